@@ -0,0 +1,149 @@
+/// Represents a color reported by the terminal.
+///
+/// Each component is scaled to 16 bits, the precision used by X11 color specifications.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RgbColor {
+    pub red: u16,
+    pub green: u16,
+    pub blue: u16,
+}
+
+impl RgbColor {
+    pub const fn new(red: u16, green: u16, blue: u16) -> Self {
+        Self { red, green, blue }
+    }
+
+    /// Returns the color with each component truncated to 8 bits.
+    pub const fn to_rgb8(self) -> (u8, u8, u8) {
+        ((self.red >> 8) as u8, (self.green >> 8) as u8, (self.blue >> 8) as u8)
+    }
+
+    /// Returns the relative luminance of the color, from `0.0` for black to `1.0` for white.
+    ///
+    /// See <https://www.w3.org/TR/WCAG21/#dfn-relative-luminance>
+    pub fn luminance(self) -> f64 {
+        let linear = |component: u16| {
+            let c = f64::from(component) / f64::from(u16::MAX);
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+
+        0.2126 * linear(self.red) + 0.7152 * linear(self.green) + 0.0722 * linear(self.blue)
+    }
+
+    /// Parses an X11 color specification, as used in replies to color queries.
+    ///
+    /// The `rgb:R/G/B` and `rgba:R/G/B/A` forms take 1 to 4 hex digits per component, which are
+    /// scaled to 16 bits. The `#RGB` form takes 1 to 4 hex digits per component, which are the
+    /// most significant bits of the component. The alpha component is ignored.
+    pub(crate) fn parse(spec: &[u8]) -> Option<Self> {
+        if let Some(spec) = spec.strip_prefix(b"rgb:") {
+            return match components(spec)[..] {
+                [r, g, b] => Some(Self::new(scale(r)?, scale(g)?, scale(b)?)),
+                _ => None,
+            };
+        }
+
+        if let Some(spec) = spec.strip_prefix(b"rgba:") {
+            return match components(spec)[..] {
+                [r, g, b, _] => Some(Self::new(scale(r)?, scale(g)?, scale(b)?)),
+                _ => None,
+            };
+        }
+
+        if let Some(spec) = spec.strip_prefix(b"#") {
+            if spec.is_empty() || spec.len() % 3 != 0 || spec.len() > 12 {
+                return None;
+            }
+
+            let n = spec.len() / 3;
+            let component = |i: usize| {
+                let value = hex(&spec[i * n..(i + 1) * n])?;
+                Some(value << (16 - 4 * n))
+            };
+
+            return Some(Self::new(component(0)?, component(1)?, component(2)?));
+        }
+
+        None
+    }
+}
+
+fn components(spec: &[u8]) -> Vec<&[u8]> {
+    spec.split(|&b| b == b'/').collect()
+}
+
+/// Parses 1 to 4 hex digits.
+fn hex(digits: &[u8]) -> Option<u16> {
+    if digits.is_empty() || digits.len() > 4 {
+        return None;
+    }
+
+    digits.iter().try_fold(0u16, |acc, &b| Some(acc << 4 | (b as char).to_digit(16)? as u16))
+}
+
+/// Parses 1 to 4 hex digits, scaling them to 16 bits.
+fn scale(digits: &[u8]) -> Option<u16> {
+    let value = u32::from(hex(digits)?);
+    let max = (1u32 << (4 * digits.len())) - 1;
+    Some((value * 0xFFFF / max) as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rgb() {
+        assert_eq!(RgbColor::parse(b"rgb:ffff/8080/0000"), Some(RgbColor::new(0xffff, 0x8080, 0)));
+        assert_eq!(RgbColor::parse(b"rgb:ff/80/00"), Some(RgbColor::new(0xffff, 0x8080, 0)));
+        assert_eq!(RgbColor::parse(b"rgb:f/8/0"), Some(RgbColor::new(0xffff, 0x8888, 0)));
+        assert_eq!(RgbColor::parse(b"rgb:fff/8/00"), Some(RgbColor::new(0xffff, 0x8888, 0)));
+    }
+
+    #[test]
+    fn parse_rgba() {
+        assert_eq!(
+            RgbColor::parse(b"rgba:ffff/0000/ffff/8000"),
+            Some(RgbColor::new(0xffff, 0, 0xffff))
+        );
+    }
+
+    #[test]
+    fn parse_hash() {
+        assert_eq!(RgbColor::parse(b"#f80"), Some(RgbColor::new(0xf000, 0x8000, 0)));
+        assert_eq!(RgbColor::parse(b"#ff8000"), Some(RgbColor::new(0xff00, 0x8000, 0)));
+        assert_eq!(RgbColor::parse(b"#ffff80000000"), Some(RgbColor::new(0xffff, 0x8000, 0)));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert_eq!(RgbColor::parse(b""), None);
+        assert_eq!(RgbColor::parse(b"red"), None);
+        assert_eq!(RgbColor::parse(b"rgb:ff/ff"), None);
+        assert_eq!(RgbColor::parse(b"rgb:ff/ff/ff/ff"), None);
+        assert_eq!(RgbColor::parse(b"rgb:fffff/0/0"), None);
+        assert_eq!(RgbColor::parse(b"rgb:gg/0/0"), None);
+        assert_eq!(RgbColor::parse(b"rgb://"), None);
+        assert_eq!(RgbColor::parse(b"#"), None);
+        assert_eq!(RgbColor::parse(b"#ffff"), None);
+        assert_eq!(RgbColor::parse(b"#fffffffffffffff"), None);
+    }
+
+    #[test]
+    fn to_rgb8() {
+        assert_eq!(RgbColor::new(0xffff, 0x80ff, 0x00ff).to_rgb8(), (0xff, 0x80, 0x00));
+    }
+
+    #[test]
+    fn luminance() {
+        assert_eq!(RgbColor::new(0, 0, 0).luminance(), 0.0);
+        assert!((RgbColor::new(0xffff, 0xffff, 0xffff).luminance() - 1.0).abs() < 1e-9);
+        assert!(
+            RgbColor::new(0xffff, 0xffff, 0).luminance() > RgbColor::new(0, 0, 0xffff).luminance()
+        );
+    }
+}
@@ -0,0 +1,323 @@
+use bitflags::bitflags;
+
+/// Represents a key event.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KeyEvent {
+    /// The key itself.
+    pub code: KeyCode,
+    /// Additional key modifiers.
+    pub modifiers: KeyModifiers,
+    /// Kind of event.
+    ///
+    /// Only set to something other than [`KeyEventKind::Press`] if
+    /// [`KeyboardEnhancementFlags::REPORT_EVENT_TYPES`] has been enabled.
+    ///
+    /// [`KeyboardEnhancementFlags::REPORT_EVENT_TYPES`]: crate::event::KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+    pub kind: KeyEventKind,
+    /// The key produced when the key is pressed with shift.
+    ///
+    /// Only reported if [`KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS`] has been enabled.
+    ///
+    /// [`KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS`]: crate::event::KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS
+    pub shifted_key: Option<KeyCode>,
+    /// The key at the same physical location in the standard PC-101 layout.
+    ///
+    /// Only reported if [`KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS`] has been enabled.
+    ///
+    /// [`KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS`]: crate::event::KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS
+    pub base_layout_key: Option<KeyCode>,
+    /// The text generated by the key event.
+    ///
+    /// Only reported if [`KeyboardEnhancementFlags::REPORT_ASSOCIATED_TEXT`] has been enabled.
+    ///
+    /// [`KeyboardEnhancementFlags::REPORT_ASSOCIATED_TEXT`]: crate::event::KeyboardEnhancementFlags::REPORT_ASSOCIATED_TEXT
+    pub text: Option<String>,
+}
+
+impl KeyEvent {
+    /// Creates a key press event with no alternate keys or associated text.
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            shifted_key: None,
+            base_layout_key: None,
+            text: None,
+        }
+    }
+
+    /// Returns `true` if `event` is a press or repeat of the key described by `self`.
+    ///
+    /// Release events never match. The kind of `self` and the associated text are ignored, as
+    /// are lock modifiers unless `self` includes them.
+    pub fn matches(&self, event: &KeyEvent, mode: MatchMode) -> bool {
+        if event.kind == KeyEventKind::Release {
+            return false;
+        }
+
+        let locks = (KeyModifiers::CAPS_LOCK | KeyModifiers::NUM_LOCK) - self.modifiers;
+        let modifiers = event.modifiers - locks;
+
+        let matches = |code: KeyCode, modifiers: KeyModifiers| {
+            code == self.code && modifiers == self.modifiers
+        };
+
+        if matches(event.code, modifiers) {
+            return true;
+        }
+
+        if mode == MatchMode::Literal {
+            return false;
+        }
+
+        // The key at the same position in the base layout, e.g. `ctrl+c` on a Cyrillic layout.
+        if event.base_layout_key.is_some_and(|code| matches(code, modifiers)) {
+            return true;
+        }
+
+        // The shifted key, e.g. `shift+a` and `A` are the same key.
+        let unshifted = modifiers - KeyModifiers::SHIFT;
+        match event.shifted_key {
+            Some(code) if modifiers.contains(KeyModifiers::SHIFT) => matches(code, unshifted),
+            Some(_) => false,
+            None => match event.code {
+                // Legacy encodings report the shifted character without the shift modifier.
+                KeyCode::Char(c) if !modifiers.contains(KeyModifiers::SHIFT) => {
+                    single_case(c.to_lowercase()).is_some_and(|lower| {
+                        lower != c && matches(KeyCode::Char(lower), modifiers | KeyModifiers::SHIFT)
+                    })
+                }
+                KeyCode::Char(c) => single_case(c.to_uppercase())
+                    .is_some_and(|upper| upper != c && matches(KeyCode::Char(upper), unshifted)),
+                _ => false,
+            },
+        }
+    }
+}
+
+/// Returns the only character of a case mapping, if it maps to a single character.
+fn single_case(mut chars: impl Iterator<Item = char>) -> Option<char> {
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+/// Represents how a key pattern is matched against key events.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MatchMode {
+    /// Match the key exactly as reported by the terminal.
+    Literal,
+    /// Also match shifted keys and keys in the base layout, when reported.
+    ///
+    /// For example, `shift+a` matches `A`, and `ctrl+c` matches `ctrl+с` on a Cyrillic layout if
+    /// [`KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS`] has been enabled.
+    ///
+    /// [`KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS`]: crate::event::KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS
+    #[default]
+    LayoutAware,
+}
+
+bitflags! {
+    /// Represents key modifiers (shift, control, alt, etc.) and lock key states.
+    ///
+    /// The bits are the same as those used by the kitty keyboard protocol.
+    ///
+    /// See <https://sw.kovidgoyal.net/kitty/keyboard-protocol/#modifiers> for more information.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    #[repr(transparent)]
+    pub struct KeyModifiers: u8 {
+        const SHIFT = 1 << 0;
+        const ALT = 1 << 1;
+        const CONTROL = 1 << 2;
+        const SUPER = 1 << 3;
+        const HYPER = 1 << 4;
+        const META = 1 << 5;
+        const CAPS_LOCK = 1 << 6;
+        const NUM_LOCK = 1 << 7;
+    }
+}
+
+/// Represents a kind of key event.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum KeyEventKind {
+    #[default]
+    Press,
+    Repeat,
+    Release,
+}
+
+/// Represents a key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeyCode {
+    /// A character key.
+    Char(char),
+    Escape,
+    Enter,
+    Tab,
+    Backspace,
+    Insert,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    CapsLock,
+    ScrollLock,
+    NumLock,
+    PrintScreen,
+    Pause,
+    Menu,
+    /// A function key, `F(1)` through `F(35)`.
+    F(u8),
+    /// A key on the keypad.
+    Keypad(KeypadKey),
+    /// A media key.
+    Media(MediaKey),
+    /// A modifier key.
+    Modifier(ModifierKey),
+}
+
+/// Represents a key on the keypad.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeypadKey {
+    /// A digit key, `Digit(0)` through `Digit(9)`.
+    Digit(u8),
+    Decimal,
+    Divide,
+    Multiply,
+    Subtract,
+    Add,
+    Enter,
+    Equal,
+    Separator,
+    Left,
+    Right,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Insert,
+    Delete,
+    Begin,
+}
+
+/// Represents a media key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MediaKey {
+    Play,
+    Pause,
+    PlayPause,
+    Reverse,
+    Stop,
+    FastForward,
+    Rewind,
+    TrackNext,
+    TrackPrevious,
+    Record,
+    LowerVolume,
+    RaiseVolume,
+    MuteVolume,
+}
+
+/// Represents a modifier key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ModifierKey {
+    LeftShift,
+    LeftControl,
+    LeftAlt,
+    LeftSuper,
+    LeftHyper,
+    LeftMeta,
+    RightShift,
+    RightControl,
+    RightAlt,
+    RightSuper,
+    RightHyper,
+    RightMeta,
+    IsoLevel3Shift,
+    IsoLevel5Shift,
+}
+
+impl KeyCode {
+    /// Converts a key number used by the kitty keyboard protocol into a key code.
+    ///
+    /// See <https://sw.kovidgoyal.net/kitty/keyboard-protocol/#functional-key-definitions>
+    pub(crate) fn from_kitty_number(number: u32) -> Option<KeyCode> {
+        let code = match number {
+            9 => KeyCode::Tab,
+            13 => KeyCode::Enter,
+            27 => KeyCode::Escape,
+            127 => KeyCode::Backspace,
+
+            57358 => KeyCode::CapsLock,
+            57359 => KeyCode::ScrollLock,
+            57360 => KeyCode::NumLock,
+            57361 => KeyCode::PrintScreen,
+            57362 => KeyCode::Pause,
+            57363 => KeyCode::Menu,
+
+            57364..=57398 => KeyCode::F((number - 57364 + 1) as u8),
+
+            57399..=57408 => KeyCode::Keypad(KeypadKey::Digit((number - 57399) as u8)),
+            57409 => KeyCode::Keypad(KeypadKey::Decimal),
+            57410 => KeyCode::Keypad(KeypadKey::Divide),
+            57411 => KeyCode::Keypad(KeypadKey::Multiply),
+            57412 => KeyCode::Keypad(KeypadKey::Subtract),
+            57413 => KeyCode::Keypad(KeypadKey::Add),
+            57414 => KeyCode::Keypad(KeypadKey::Enter),
+            57415 => KeyCode::Keypad(KeypadKey::Equal),
+            57416 => KeyCode::Keypad(KeypadKey::Separator),
+            57417 => KeyCode::Keypad(KeypadKey::Left),
+            57418 => KeyCode::Keypad(KeypadKey::Right),
+            57419 => KeyCode::Keypad(KeypadKey::Up),
+            57420 => KeyCode::Keypad(KeypadKey::Down),
+            57421 => KeyCode::Keypad(KeypadKey::PageUp),
+            57422 => KeyCode::Keypad(KeypadKey::PageDown),
+            57423 => KeyCode::Keypad(KeypadKey::Home),
+            57424 => KeyCode::Keypad(KeypadKey::End),
+            57425 => KeyCode::Keypad(KeypadKey::Insert),
+            57426 => KeyCode::Keypad(KeypadKey::Delete),
+            57427 => KeyCode::Keypad(KeypadKey::Begin),
+
+            57428 => KeyCode::Media(MediaKey::Play),
+            57429 => KeyCode::Media(MediaKey::Pause),
+            57430 => KeyCode::Media(MediaKey::PlayPause),
+            57431 => KeyCode::Media(MediaKey::Reverse),
+            57432 => KeyCode::Media(MediaKey::Stop),
+            57433 => KeyCode::Media(MediaKey::FastForward),
+            57434 => KeyCode::Media(MediaKey::Rewind),
+            57435 => KeyCode::Media(MediaKey::TrackNext),
+            57436 => KeyCode::Media(MediaKey::TrackPrevious),
+            57437 => KeyCode::Media(MediaKey::Record),
+            57438 => KeyCode::Media(MediaKey::LowerVolume),
+            57439 => KeyCode::Media(MediaKey::RaiseVolume),
+            57440 => KeyCode::Media(MediaKey::MuteVolume),
+
+            57441 => KeyCode::Modifier(ModifierKey::LeftShift),
+            57442 => KeyCode::Modifier(ModifierKey::LeftControl),
+            57443 => KeyCode::Modifier(ModifierKey::LeftAlt),
+            57444 => KeyCode::Modifier(ModifierKey::LeftSuper),
+            57445 => KeyCode::Modifier(ModifierKey::LeftHyper),
+            57446 => KeyCode::Modifier(ModifierKey::LeftMeta),
+            57447 => KeyCode::Modifier(ModifierKey::RightShift),
+            57448 => KeyCode::Modifier(ModifierKey::RightControl),
+            57449 => KeyCode::Modifier(ModifierKey::RightAlt),
+            57450 => KeyCode::Modifier(ModifierKey::RightSuper),
+            57451 => KeyCode::Modifier(ModifierKey::RightHyper),
+            57452 => KeyCode::Modifier(ModifierKey::RightMeta),
+            57453 => KeyCode::Modifier(ModifierKey::IsoLevel3Shift),
+            57454 => KeyCode::Modifier(ModifierKey::IsoLevel5Shift),
+
+            _ => KeyCode::Char(char::from_u32(number)?),
+        };
+        Some(code)
+    }
+}
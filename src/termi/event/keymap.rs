@@ -0,0 +1,430 @@
+use std::collections::VecDeque;
+use std::io;
+use std::time::{Duration, Instant};
+
+use crate::event::{Event, EventLoop, KeyCode, KeyEvent, KeyEventKind, MatchMode, ParseKeyError};
+
+/// The default time to wait for the next key of a chord.
+pub const DEFAULT_CHORD_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Maps sequences of key events to actions.
+///
+/// Bindings are stored in a trie, so a binding may be a prefix of another, e.g. `ctrl+x` and
+/// `ctrl+x ctrl+s`. In that case the shorter binding is only matched once the next key does not
+/// continue the longer one, or the chord times out.
+pub struct Keymap<A> {
+    root: Node<A>,
+    mode: MatchMode,
+    chord_timeout: Duration,
+    /// Keys received that are a prefix of at least one binding.
+    pending: Vec<KeyEvent>,
+    /// Key events that never take part in a binding, received while keys were pending.
+    ///
+    /// Each is held along with the number of pending keys received before it, so that it is
+    /// returned once those keys are resolved.
+    passed: Vec<(usize, KeyEvent)>,
+    last_key: Instant,
+    events: VecDeque<KeymapEvent<A>>,
+}
+
+struct Node<A> {
+    action: Option<A>,
+    children: Vec<(KeyEvent, Node<A>)>,
+}
+
+impl<A> Node<A> {
+    fn new() -> Self {
+        Self { action: None, children: Vec::new() }
+    }
+}
+
+struct Walk<'a, A> {
+    /// The number of keys matched.
+    matched: usize,
+    /// The node reached by the matched keys.
+    node: &'a Node<A>,
+    /// The length and action of the longest complete binding along the way.
+    complete: Option<(usize, &'a A)>,
+}
+
+/// Represents the output of a [`Keymap`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeymapEvent<A> {
+    /// A binding was matched.
+    Action(A),
+    /// An event that is not part of a binding.
+    Event(Event),
+}
+
+/// Represents how far the pending keys are through a chord.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeymapState {
+    /// No keys are pending.
+    Idle,
+    /// The pending keys are a prefix of one or more bindings.
+    Partial,
+    /// The pending keys are a complete binding, but also a prefix of one or more longer bindings.
+    Ambiguous,
+}
+
+impl<A: Clone> Keymap<A> {
+    pub fn new() -> Self {
+        Self {
+            root: Node::new(),
+            mode: MatchMode::default(),
+            chord_timeout: DEFAULT_CHORD_TIMEOUT,
+            pending: Vec::new(),
+            passed: Vec::new(),
+            last_key: Instant::now(),
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Sets how key patterns are matched against key events.
+    pub fn set_match_mode(&mut self, mode: MatchMode) {
+        self.mode = mode;
+    }
+
+    /// Returns the time to wait for the next key of a chord.
+    pub fn chord_timeout(&self) -> Duration {
+        self.chord_timeout
+    }
+
+    /// Sets the time to wait for the next key of a chord.
+    pub fn set_chord_timeout(&mut self, timeout: Duration) {
+        self.chord_timeout = timeout;
+    }
+
+    /// Binds a sequence of keys to an action, returning the action previously bound to them.
+    pub fn insert(&mut self, keys: &[KeyEvent], action: A) -> Option<A> {
+        let mut node = &mut self.root;
+        for key in keys {
+            let i = match node.children.iter().position(|(k, _)| k == key) {
+                Some(i) => i,
+                None => {
+                    node.children.push((key.clone(), Node::new()));
+                    node.children.len() - 1
+                }
+            };
+            node = &mut node.children[i].1;
+        }
+        node.action.replace(action)
+    }
+
+    /// Binds a whitespace separated sequence of keys, such as `ctrl+x ctrl+s`, to an action.
+    ///
+    /// See [`KeyEvent`]'s [`Display`](core::fmt::Display) implementation for the key notation.
+    pub fn bind(&mut self, keys: &str, action: A) -> Result<Option<A>, ParseKeyError> {
+        let keys = keys.split_whitespace().map(str::parse).collect::<Result<Vec<_>, _>>()?;
+        Ok(self.insert(&keys, action))
+    }
+
+    /// Returns the keys received that are a prefix of at least one binding.
+    pub fn pending(&self) -> &[KeyEvent] {
+        &self.pending
+    }
+
+    /// Returns how far the pending keys are through a chord.
+    pub fn state(&self) -> KeymapState {
+        if self.pending.is_empty() {
+            KeymapState::Idle
+        } else if self.walk(&self.pending).node.action.is_some() {
+            KeymapState::Ambiguous
+        } else {
+            KeymapState::Partial
+        }
+    }
+
+    /// Walks the trie along `keys`, stopping at the first key that does not match.
+    ///
+    /// An exact match is preferred over a layout-aware one, so `A` is not taken by `shift+a` when
+    /// both are bound.
+    fn walk(&self, keys: &[KeyEvent]) -> Walk<'_, A> {
+        let mut walk = Walk { matched: 0, node: &self.root, complete: None };
+
+        for key in keys {
+            let children = &walk.node.children;
+            let child = children
+                .iter()
+                .find(|(pattern, _)| pattern.matches(key, MatchMode::Literal))
+                .or_else(|| children.iter().find(|(pattern, _)| pattern.matches(key, self.mode)));
+
+            match child {
+                Some((_, child)) => walk.node = child,
+                None => break,
+            }
+
+            walk.matched += 1;
+            if let Some(action) = &walk.node.action {
+                walk.complete = Some((walk.matched, action));
+            }
+        }
+
+        walk
+    }
+
+    /// Feeds a key event into the keymap.
+    ///
+    /// Any resulting events are returned by iterating over the keymap. Key releases and presses of
+    /// modifier keys never take part in a binding and are passed through, after any keys that
+    /// were pending when they were received.
+    pub fn feed(&mut self, key: KeyEvent) {
+        if key.kind == KeyEventKind::Release || matches!(key.code, KeyCode::Modifier(_)) {
+            if self.pending.is_empty() {
+                self.events.push_back(KeymapEvent::Event(Event::Key(key)));
+            } else {
+                self.passed.push((self.pending.len(), key));
+            }
+            return;
+        }
+
+        self.pending.push(key);
+        self.last_key = Instant::now();
+        self.resolve(false);
+    }
+
+    /// Resolves the pending keys as if the chord had timed out.
+    pub fn flush(&mut self) {
+        self.resolve(true);
+    }
+
+    /// Consumes pending keys until they are a strict prefix of a longer binding.
+    fn resolve(&mut self, flush: bool) {
+        while !self.pending.is_empty() {
+            let walk = self.walk(&self.pending);
+
+            let has_children = walk.matched == self.pending.len() && !walk.node.children.is_empty();
+
+            if has_children && !flush {
+                return;
+            }
+
+            let (len, event) = match walk.complete {
+                Some((len, action)) => (len, KeymapEvent::Action(action.clone())),
+                None => (1, KeymapEvent::Event(Event::Key(self.pending[0].clone()))),
+            };
+
+            self.pending.drain(..len);
+            self.events.push_back(event);
+            self.release_passed(len);
+        }
+    }
+
+    /// Returns the passed through events that were received before the first `resolved` pending
+    /// keys were resolved.
+    fn release_passed(&mut self, resolved: usize) {
+        let released = self.passed.iter().take_while(|(before, _)| *before <= resolved).count();
+        for (_, key) in self.passed.drain(..released) {
+            self.events.push_back(KeymapEvent::Event(Event::Key(key)));
+        }
+
+        for (before, _) in &mut self.passed {
+            *before -= resolved;
+        }
+    }
+
+    /// Reads the next event from `event_loop`, matching key events against the bindings.
+    ///
+    /// Events other than key events are passed through, after resolving any pending keys so that
+    /// events are returned in the order they were received. If a chord is not continued within
+    /// the chord timeout, the pending keys are resolved on their own.
+    pub fn read(&mut self, event_loop: &mut EventLoop) -> io::Result<KeymapEvent<A>> {
+        let filter = |_: &Event| true;
+
+        loop {
+            if let Some(event) = self.next() {
+                return Ok(event);
+            }
+
+            if !self.pending.is_empty() {
+                let leftover = self.chord_timeout.saturating_sub(self.last_key.elapsed());
+                if !event_loop.poll(Some(leftover), &filter)? {
+                    self.flush();
+                    continue;
+                }
+            }
+
+            match event_loop.read(&filter)? {
+                Event::Key(key) => self.feed(key),
+                event => {
+                    self.flush();
+                    self.events.push_back(KeymapEvent::Event(event));
+                }
+            }
+        }
+    }
+}
+
+impl<A: Clone> Default for Keymap<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A> Iterator for Keymap<A> {
+    type Item = KeymapEvent<A>;
+
+    fn next(&mut self) -> Option<KeymapEvent<A>> {
+        self.events.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::KeyModifiers;
+
+    fn key(notation: &str) -> KeyEvent {
+        notation.parse().unwrap()
+    }
+
+    fn keymap_with(bindings: &[(&str, &'static str)]) -> Keymap<&'static str> {
+        let mut keymap = Keymap::new();
+        for &(keys, action) in bindings {
+            keymap.bind(keys, action).unwrap();
+        }
+        keymap
+    }
+
+    fn feed(keymap: &mut Keymap<&'static str>, keys: &str) -> Vec<KeymapEvent<&'static str>> {
+        for notation in keys.split_whitespace() {
+            keymap.feed(key(notation));
+        }
+        keymap.collect()
+    }
+
+    fn action(action: &'static str) -> KeymapEvent<&'static str> {
+        KeymapEvent::Action(action)
+    }
+
+    fn event(notation: &str) -> KeymapEvent<&'static str> {
+        KeymapEvent::Event(Event::Key(key(notation)))
+    }
+
+    #[test]
+    fn feed_single_key() {
+        let mut keymap = keymap_with(&[("ctrl+s", "save")]);
+
+        assert_eq!(feed(&mut keymap, "ctrl+s"), [action("save")]);
+        assert_eq!(feed(&mut keymap, "a"), [event("a")]);
+        assert_eq!(keymap.state(), KeymapState::Idle);
+    }
+
+    #[test]
+    fn feed_chord() {
+        let mut keymap = keymap_with(&[("ctrl+x ctrl+s", "save"), ("ctrl+x ctrl+c", "quit")]);
+
+        assert_eq!(feed(&mut keymap, "ctrl+x"), []);
+        assert_eq!(keymap.state(), KeymapState::Partial);
+        assert_eq!(keymap.pending(), [key("ctrl+x")]);
+
+        assert_eq!(feed(&mut keymap, "ctrl+c"), [action("quit")]);
+        assert_eq!(keymap.state(), KeymapState::Idle);
+    }
+
+    #[test]
+    fn feed_broken_chord() {
+        let mut keymap = keymap_with(&[("ctrl+x ctrl+s", "save"), ("a", "a")]);
+
+        assert_eq!(feed(&mut keymap, "ctrl+x b"), [event("ctrl+x"), event("b")]);
+        assert_eq!(feed(&mut keymap, "ctrl+x a"), [event("ctrl+x"), action("a")]);
+    }
+
+    #[test]
+    fn feed_prefix_binding() {
+        let mut keymap = keymap_with(&[("ctrl+x", "cut"), ("ctrl+x ctrl+s", "save")]);
+
+        assert_eq!(feed(&mut keymap, "ctrl+x"), []);
+        assert_eq!(keymap.state(), KeymapState::Ambiguous);
+
+        assert_eq!(feed(&mut keymap, "b"), [action("cut"), event("b")]);
+        assert_eq!(feed(&mut keymap, "ctrl+x ctrl+s"), [action("save")]);
+    }
+
+    #[test]
+    fn feed_passes_through_releases_and_modifiers() {
+        let mut keymap = keymap_with(&[("ctrl+x ctrl+s", "save")]);
+
+        let release = KeyEvent { kind: KeyEventKind::Release, ..key("ctrl+x") };
+        let modifier = key("left_control");
+
+        keymap.feed(release.clone());
+        assert_eq!(keymap.next(), Some(KeymapEvent::Event(Event::Key(release.clone()))));
+
+        keymap.feed(key("ctrl+x"));
+        keymap.feed(release.clone());
+        keymap.feed(modifier.clone());
+        assert_eq!(keymap.next(), None);
+        assert_eq!(keymap.state(), KeymapState::Partial);
+
+        assert_eq!(
+            feed(&mut keymap, "b"),
+            [
+                event("ctrl+x"),
+                KeymapEvent::Event(Event::Key(release.clone())),
+                KeymapEvent::Event(Event::Key(modifier)),
+                event("b"),
+            ]
+        );
+
+        keymap.feed(key("ctrl+x"));
+        keymap.feed(release.clone());
+        assert_eq!(
+            feed(&mut keymap, "ctrl+s"),
+            [action("save"), KeymapEvent::Event(Event::Key(release))]
+        );
+    }
+
+    #[test]
+    fn flush() {
+        let mut keymap =
+            keymap_with(&[("ctrl+x", "cut"), ("ctrl+x ctrl+s", "save"), ("g g", "top")]);
+
+        feed(&mut keymap, "ctrl+x");
+        keymap.flush();
+        assert_eq!(keymap.by_ref().collect::<Vec<_>>(), [action("cut")]);
+        assert_eq!(keymap.state(), KeymapState::Idle);
+
+        feed(&mut keymap, "g");
+        keymap.flush();
+        assert_eq!(keymap.by_ref().collect::<Vec<_>>(), [event("g")]);
+
+        keymap.flush();
+        assert_eq!(keymap.next(), None);
+    }
+
+    #[test]
+    fn rebind_replaces_action() {
+        let mut keymap = keymap_with(&[("ctrl+s", "save")]);
+
+        assert_eq!(keymap.bind("ctrl+s", "write"), Ok(Some("save")));
+        assert_eq!(feed(&mut keymap, "ctrl+s"), [action("write")]);
+    }
+
+    #[test]
+    fn exact_match_preferred() {
+        let mut keymap = keymap_with(&[("shift+a", "shifted"), ("A", "upper")]);
+
+        assert_eq!(feed(&mut keymap, "A"), [action("upper")]);
+        assert_eq!(feed(&mut keymap, "shift+a"), [action("shifted")]);
+
+        let mut keymap = keymap_with(&[("shift+a", "shifted")]);
+        assert_eq!(feed(&mut keymap, "A"), [action("shifted")]);
+
+        keymap.set_match_mode(MatchMode::Literal);
+        assert_eq!(feed(&mut keymap, "A"), [event("A")]);
+    }
+
+    #[test]
+    fn layout_aware_base_key() {
+        let mut keymap = keymap_with(&[("ctrl+c", "copy")]);
+
+        let key = KeyEvent {
+            base_layout_key: Some(KeyCode::Char('c')),
+            ..KeyEvent::new(KeyCode::Char('с'), KeyModifiers::CONTROL)
+        };
+        keymap.feed(key);
+
+        assert_eq!(keymap.collect::<Vec<_>>(), [action("copy")]);
+    }
+}
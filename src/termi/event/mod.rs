@@ -1,19 +1,51 @@
+mod color;
+mod key;
+mod keymap;
+mod mouse;
+mod notation;
 mod parser;
 mod read;
 
 use bitflags::bitflags;
 
+pub use self::color::*;
+pub use self::key::*;
+pub use self::keymap::*;
+pub use self::mouse::*;
+pub use self::notation::*;
 pub use self::parser::*;
 pub use self::read::*;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Event {
-    // Key(KeyEvent),
-    // Mouse(MouseEvent),
-    // Paste(String),
+    Key(KeyEvent),
+    Mouse(MouseEvent),
+    /// A bracketed paste.
+    Paste(String),
+    /// A bracketed paste that was not valid UTF-8, with the raw bytes preserved.
+    InvalidPaste(Vec<u8>),
+    /// The terminal gained focus.
+    FocusGained,
+    /// The terminal lost focus.
+    FocusLost,
     KeyboardEnhancmentFlags(KeyboardEnhancementFlags),
-    PrimaryDeviceAttributes,
+    PrimaryDeviceAttributes(PrimaryDeviceAttributes),
+    SecondaryDeviceAttributes(SecondaryDeviceAttributes),
+    TerminalVersion(TerminalVersion),
+    Termcap(TermcapReply),
+    StatusString(StatusStringReply),
+    KittyGraphics(KittyGraphicsReply),
+    GraphicsAttribute(GraphicsAttributeReport),
+    /// The color scheme of the terminal, in reply to a query or after it changed.
+    ThemeChanged(Theme),
+    ModeReport(ModeReport),
+    DynamicColor(DynamicColor),
+    PaletteColor(PaletteColor),
+    CursorPosition(CursorPosition),
+    WindowSize(WindowSizeReport),
     DesktopNotifications(DesktopNotificationsSupport),
+    /// A sequence that was not recognized by the parser.
+    Unknown(UnknownSequence),
 }
 
 bitflags! {
@@ -31,8 +63,403 @@ bitflags! {
     }
 }
 
+/// Represents the primary device attributes (DA1) reported by the terminal.
+///
+/// See <https://vt100.net/docs/vt510-rm/DA1.html> for more information.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrimaryDeviceAttributes {
+    /// The conformance level, e.g. `62` for a VT220 or `1` for a VT100.
+    pub conformance_level: u16,
+    /// The features reported by the terminal.
+    ///
+    /// Only reported by terminals with a conformance level of at least `62` (VT220).
+    pub features: DeviceFeatures,
+    /// The raw parameters of the reply, including the conformance level.
+    pub params: Vec<u16>,
+}
+
+bitflags! {
+    /// Represents features reported in the primary device attributes.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct DeviceFeatures: u32 {
+        const COLUMNS_132 = 1 << 0;
+        const PRINTER = 1 << 1;
+        const REGIS_GRAPHICS = 1 << 2;
+        const SIXEL_GRAPHICS = 1 << 3;
+        const SELECTIVE_ERASE = 1 << 4;
+        const SOFT_CHARACTER_SET = 1 << 5;
+        const USER_DEFINED_KEYS = 1 << 6;
+        const NATIONAL_REPLACEMENT_CHARACTER_SETS = 1 << 7;
+        const TECHNICAL_CHARACTERS = 1 << 8;
+        const LOCATOR_PORT = 1 << 9;
+        const TERMINAL_STATE_INTERROGATION = 1 << 10;
+        const USER_WINDOWS = 1 << 11;
+        const HORIZONTAL_SCROLLING = 1 << 12;
+        const ANSI_COLOR = 1 << 13;
+        const GREEK = 1 << 14;
+        const TURKISH = 1 << 15;
+        const RECTANGULAR_EDITING = 1 << 16;
+        const ANSI_TEXT_LOCATOR = 1 << 17;
+        const ISO_LATIN_2 = 1 << 18;
+        const PC_TERM = 1 << 19;
+        const SOFT_KEY_MAP = 1 << 20;
+        const ASCII_EMULATION = 1 << 21;
+    }
+}
+
+impl DeviceFeatures {
+    /// Converts a feature code reported in the primary device attributes into a feature.
+    pub(crate) fn from_code(code: u16) -> Option<Self> {
+        let feature = match code {
+            1 => Self::COLUMNS_132,
+            2 => Self::PRINTER,
+            3 => Self::REGIS_GRAPHICS,
+            4 => Self::SIXEL_GRAPHICS,
+            6 => Self::SELECTIVE_ERASE,
+            7 => Self::SOFT_CHARACTER_SET,
+            8 => Self::USER_DEFINED_KEYS,
+            9 => Self::NATIONAL_REPLACEMENT_CHARACTER_SETS,
+            15 => Self::TECHNICAL_CHARACTERS,
+            16 => Self::LOCATOR_PORT,
+            17 => Self::TERMINAL_STATE_INTERROGATION,
+            18 => Self::USER_WINDOWS,
+            21 => Self::HORIZONTAL_SCROLLING,
+            22 => Self::ANSI_COLOR,
+            23 => Self::GREEK,
+            24 => Self::TURKISH,
+            28 => Self::RECTANGULAR_EDITING,
+            29 => Self::ANSI_TEXT_LOCATOR,
+            42 => Self::ISO_LATIN_2,
+            44 => Self::PC_TERM,
+            45 => Self::SOFT_KEY_MAP,
+            46 => Self::ASCII_EMULATION,
+            _ => return None,
+        };
+        Some(feature)
+    }
+}
+
+/// Represents the secondary device attributes (DA2) reported by the terminal.
+///
+/// Many terminals identify themselves through the terminal type and firmware version, e.g. xterm
+/// reports its patch level and VTE reports its version as `MAJOR * 10000 + MINOR * 100 + MICRO`.
+///
+/// See <https://vt100.net/docs/vt510-rm/DA2.html> for more information.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SecondaryDeviceAttributes {
+    /// The terminal type, e.g. `1` for a VT220 or `41` for a VT420.
+    pub terminal_type: u16,
+    /// The firmware version.
+    pub firmware_version: u32,
+    /// The ROM cartridge registration number, usually `0`.
+    pub rom_cartridge: u16,
+}
+
+/// Represents the name and version of the terminal, as reported by `XTVERSION`.
+///
+/// Most terminals reply with `name(version)`, e.g. `kitty(0.35.2)` or `XTerm(388)`, although some
+/// separate the version with a space instead, e.g. `tmux 3.4`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TerminalVersion {
+    pub name: String,
+    pub version: Option<String>,
+}
+
+/// Represents the reply to a terminfo capability query (`XTGETTCAP`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TermcapReply {
+    /// The names and values of the requested capabilities.
+    ///
+    /// Boolean capabilities have no value.
+    Valid(Vec<(String, Option<String>)>),
+    /// A requested capability is not recognized by the terminal.
+    Invalid,
+}
+
+/// Represents a setting that can be queried with `DECRQSS`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SettingKind {
+    /// Graphic rendition (`SGR`).
+    Sgr,
+    /// Cursor style (`DECSCUSR`).
+    CursorStyle,
+    /// Top and bottom margins (`DECSTBM`).
+    ScrollMargins,
+    /// Left and right margins (`DECSLRM`).
+    LeftRightMargins,
+    /// Conformance level (`DECSCL`).
+    ConformanceLevel,
+}
+
+impl SettingKind {
+    /// Returns the intermediate and final bytes of the control function for the setting.
+    pub(crate) fn function(self) -> &'static str {
+        match self {
+            SettingKind::Sgr => "m",
+            SettingKind::CursorStyle => " q",
+            SettingKind::ScrollMargins => "r",
+            SettingKind::LeftRightMargins => "s",
+            SettingKind::ConformanceLevel => "\"p",
+        }
+    }
+}
+
+/// Represents the style of the cursor, as set with `DECSCUSR`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CursorStyle {
+    /// The default style of the terminal, usually a blinking block.
+    Default,
+    BlinkingBlock,
+    SteadyBlock,
+    BlinkingUnderline,
+    SteadyUnderline,
+    BlinkingBar,
+    SteadyBar,
+}
+
+impl CursorStyle {
+    pub(crate) fn from_code(code: u16) -> Option<Self> {
+        Some(match code {
+            0 => CursorStyle::Default,
+            1 => CursorStyle::BlinkingBlock,
+            2 => CursorStyle::SteadyBlock,
+            3 => CursorStyle::BlinkingUnderline,
+            4 => CursorStyle::SteadyUnderline,
+            5 => CursorStyle::BlinkingBar,
+            6 => CursorStyle::SteadyBar,
+            _ => return None,
+        })
+    }
+
+    /// Returns the `DECSCUSR` parameter for the style.
+    pub fn code(self) -> u16 {
+        match self {
+            CursorStyle::Default => 0,
+            CursorStyle::BlinkingBlock => 1,
+            CursorStyle::SteadyBlock => 2,
+            CursorStyle::BlinkingUnderline => 3,
+            CursorStyle::SteadyUnderline => 4,
+            CursorStyle::BlinkingBar => 5,
+            CursorStyle::SteadyBar => 6,
+        }
+    }
+}
+
+/// Represents the value of a setting, as reported by `DECRQSS`.
+///
+/// Margins are zero-based and inclusive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Setting {
+    /// The `;` separated parameters of the current graphic rendition, e.g. `0;1;38:2::255:0:0`.
+    Sgr(String),
+    CursorStyle(CursorStyle),
+    ScrollMargins {
+        top: u16,
+        bottom: u16,
+    },
+    LeftRightMargins {
+        left: u16,
+        right: u16,
+    },
+    ConformanceLevel {
+        /// The conformance level, e.g. `62` for a VT220 or `65` for a VT525.
+        level: u16,
+        /// Whether 8-bit controls are used, if reported.
+        eight_bit_controls: Option<bool>,
+    },
+}
+
+/// Represents the reply to a setting query (`DECRQSS`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StatusStringReply {
+    Valid(Setting),
+    /// The requested setting is not recognized by the terminal.
+    Invalid,
+}
+
+/// Represents a reply from the kitty graphics protocol, `APC G control-data ; message ST`.
+///
+/// See <https://sw.kovidgoyal.net/kitty/graphics-protocol/#display-images-on-screen> for more
+/// information.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KittyGraphicsReply {
+    /// The image id the reply refers to, if any.
+    pub image_id: Option<u32>,
+    /// `OK` on success, or an error code followed by a description, e.g. `ENOENT:File not found`.
+    pub message: String,
+}
+
+impl KittyGraphicsReply {
+    /// Returns `true` if the reply reports success.
+    pub fn is_ok(&self) -> bool {
+        self.message == "OK"
+    }
+}
+
+/// Represents the reply to a graphics attribute query (`XTSMGRAPHICS`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GraphicsAttributeReport {
+    /// The number of color registers available for sixel and ReGIS graphics.
+    ColorRegisters(u16),
+    /// The largest sixel image, in pixels.
+    SixelGeometry { width: u16, height: u16 },
+    /// The terminal failed to report the attribute with the given item number.
+    Error(u16),
+}
+
+/// Represents the color scheme of the terminal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Theme {
+    Dark,
+    Light,
+}
+
+/// Represents a terminal mode, as set with `SM`/`RM` or `DECSET`/`DECRST`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// An ANSI mode, e.g. `4` for insert mode.
+    Ansi(u16),
+    /// A DEC private mode, e.g. `2004` for bracketed paste.
+    Dec(u16),
+}
+
+/// Represents the state of a terminal mode, as reported by `DECRPM`.
+///
+/// See <https://vt100.net/docs/vt510-rm/DECRPM.html> for more information.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModeState {
+    /// The mode is not recognized by the terminal.
+    NotRecognized,
+    Set,
+    Reset,
+    /// The mode is set and cannot be changed.
+    PermanentlySet,
+    /// The mode is reset and cannot be changed.
+    PermanentlyReset,
+}
+
+impl ModeState {
+    pub(crate) fn from_code(code: i64) -> Option<Self> {
+        Some(match code {
+            0 => ModeState::NotRecognized,
+            1 => ModeState::Set,
+            2 => ModeState::Reset,
+            3 => ModeState::PermanentlySet,
+            4 => ModeState::PermanentlyReset,
+            _ => return None,
+        })
+    }
+
+    /// Returns `true` if the mode is set, permanently or not.
+    pub fn is_set(self) -> bool {
+        matches!(self, ModeState::Set | ModeState::PermanentlySet)
+    }
+}
+
+/// Represents the reply to a `DECRQM` query.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ModeReport {
+    pub mode: Mode,
+    pub state: ModeState,
+}
+
+/// Represents a dynamic color of the terminal, as set with `OSC 10`, `OSC 11` and `OSC 12`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DynamicColorKind {
+    Foreground,
+    Background,
+    Cursor,
+}
+
+impl DynamicColorKind {
+    /// Returns the `OSC` number for the color.
+    pub(crate) fn code(self) -> u8 {
+        match self {
+            DynamicColorKind::Foreground => 10,
+            DynamicColorKind::Background => 11,
+            DynamicColorKind::Cursor => 12,
+        }
+    }
+}
+
+/// Represents the reply to a dynamic color query.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DynamicColor {
+    pub kind: DynamicColorKind,
+    pub color: RgbColor,
+}
+
+/// Represents the reply to a palette color query, `OSC 4`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PaletteColor {
+    pub index: u8,
+    pub color: RgbColor,
+}
+
+/// Represents a cursor position report (`CPR` or `DECXCPR`).
+///
+/// Positions are zero-based.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CursorPosition {
+    /// The column of the cursor.
+    pub column: u16,
+    /// The row of the cursor.
+    pub row: u16,
+    /// The page of the cursor, only reported by `DECXCPR`.
+    pub page: Option<u16>,
+}
+
+/// Represents what a window size report measures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WindowSizeKind {
+    /// The size of the text area in pixels, reported by `CSI 4 ; height ; width t`.
+    TextAreaPixels,
+    /// The size of a character cell in pixels, reported by `CSI 6 ; height ; width t`.
+    CellPixels,
+    /// The size of the text area in characters, reported by `CSI 8 ; rows ; columns t`.
+    TextAreaCells,
+}
+
+/// Represents the reply to a window size query (`XTWINOPS`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WindowSizeReport {
+    pub kind: WindowSizeKind,
+    pub width: u16,
+    pub height: u16,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct DesktopNotificationsSupport {
     pub identifier: String,
     // TODO: Support key-values.
 }
+
+/// Represents a sequence that was not recognized by the parser.
+///
+/// An escape followed by anything other than a sequence introducer is reported as an
+/// Alt-prefixed key, so escape sequences such as `ESC # 8` never appear here.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UnknownSequence {
+    /// A control sequence, `CSI params intermediates final`.
+    Csi {
+        /// The parameter bytes, including any private marker, e.g. `?1;2`.
+        params: Vec<u8>,
+        intermediates: Vec<u8>,
+        final_byte: u8,
+    },
+    /// An operating system command, `OSC params ST`.
+    Osc {
+        /// The `;` separated parameters.
+        params: Vec<Vec<u8>>,
+    },
+    /// A device control string, `DCS params intermediates final data ST`.
+    Dcs {
+        /// The parameter bytes, including any private marker, e.g. `>` or `1;2`.
+        params: Vec<u8>,
+        intermediates: Vec<u8>,
+        final_byte: u8,
+        data: Vec<u8>,
+    },
+    /// An application program command, `APC data ST`.
+    Apc { data: Vec<u8> },
+}
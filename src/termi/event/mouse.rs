@@ -0,0 +1,52 @@
+use crate::event::KeyModifiers;
+
+/// Represents a mouse event.
+///
+/// Positions are zero-based. If [`MouseEncoding::SgrPixels`] has been enabled, they are measured
+/// in pixels rather than cells.
+///
+/// [`MouseEncoding::SgrPixels`]: crate::terminal::MouseEncoding::SgrPixels
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MouseEvent {
+    /// Kind of event.
+    pub kind: MouseEventKind,
+    /// The column of the event.
+    pub column: u16,
+    /// The row of the event.
+    pub row: u16,
+    /// Key modifiers held during the event.
+    ///
+    /// Only [`KeyModifiers::SHIFT`], [`KeyModifiers::ALT`] and [`KeyModifiers::CONTROL`] are
+    /// reported.
+    pub modifiers: KeyModifiers,
+}
+
+/// Represents a kind of mouse event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MouseEventKind {
+    /// A mouse button was pressed.
+    Down(MouseButton),
+    /// A mouse button was released.
+    ///
+    /// The X10 and urxvt encodings do not report which button was released, in which case
+    /// [`MouseButton::Left`] is used.
+    Up(MouseButton),
+    /// The mouse was moved while a button was held.
+    Drag(MouseButton),
+    /// The mouse was moved without a button held.
+    Moved,
+    ScrollUp,
+    ScrollDown,
+    ScrollLeft,
+    ScrollRight,
+}
+
+/// Represents a mouse button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+    Back,
+    Forward,
+}
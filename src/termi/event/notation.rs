@@ -0,0 +1,462 @@
+use core::fmt;
+use core::str::FromStr;
+
+use crate::event::{KeyCode, KeyEvent, KeyModifiers, KeypadKey, MediaKey, ModifierKey};
+
+/// Names of modifiers, in the order they are written.
+const MODIFIERS: &[(KeyModifiers, &str)] = &[
+    (KeyModifiers::CONTROL, "ctrl"),
+    (KeyModifiers::ALT, "alt"),
+    (KeyModifiers::SHIFT, "shift"),
+    (KeyModifiers::SUPER, "super"),
+    (KeyModifiers::HYPER, "hyper"),
+    (KeyModifiers::META, "meta"),
+    (KeyModifiers::CAPS_LOCK, "caps_lock"),
+    (KeyModifiers::NUM_LOCK, "num_lock"),
+];
+
+/// Alternative names of modifiers, accepted when parsing.
+const MODIFIER_ALIASES: &[(KeyModifiers, &str)] = &[
+    (KeyModifiers::CONTROL, "control"),
+    (KeyModifiers::ALT, "option"),
+    (KeyModifiers::SUPER, "cmd"),
+];
+
+/// Keys with a name, other than characters, function keys and keypad digits.
+///
+/// Names are written by the [`Display`](fmt::Display) implementation of [`KeyCode`], and parsed by
+/// looking them up here.
+const NAMED_KEYS: &[KeyCode] = &[
+    KeyCode::Escape,
+    KeyCode::Enter,
+    KeyCode::Tab,
+    KeyCode::Backspace,
+    KeyCode::Insert,
+    KeyCode::Delete,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::Home,
+    KeyCode::End,
+    KeyCode::CapsLock,
+    KeyCode::ScrollLock,
+    KeyCode::NumLock,
+    KeyCode::PrintScreen,
+    KeyCode::Pause,
+    KeyCode::Menu,
+    KeyCode::Keypad(KeypadKey::Decimal),
+    KeyCode::Keypad(KeypadKey::Divide),
+    KeyCode::Keypad(KeypadKey::Multiply),
+    KeyCode::Keypad(KeypadKey::Subtract),
+    KeyCode::Keypad(KeypadKey::Add),
+    KeyCode::Keypad(KeypadKey::Enter),
+    KeyCode::Keypad(KeypadKey::Equal),
+    KeyCode::Keypad(KeypadKey::Separator),
+    KeyCode::Keypad(KeypadKey::Left),
+    KeyCode::Keypad(KeypadKey::Right),
+    KeyCode::Keypad(KeypadKey::Up),
+    KeyCode::Keypad(KeypadKey::Down),
+    KeyCode::Keypad(KeypadKey::PageUp),
+    KeyCode::Keypad(KeypadKey::PageDown),
+    KeyCode::Keypad(KeypadKey::Home),
+    KeyCode::Keypad(KeypadKey::End),
+    KeyCode::Keypad(KeypadKey::Insert),
+    KeyCode::Keypad(KeypadKey::Delete),
+    KeyCode::Keypad(KeypadKey::Begin),
+    KeyCode::Media(MediaKey::Play),
+    KeyCode::Media(MediaKey::Pause),
+    KeyCode::Media(MediaKey::PlayPause),
+    KeyCode::Media(MediaKey::Reverse),
+    KeyCode::Media(MediaKey::Stop),
+    KeyCode::Media(MediaKey::FastForward),
+    KeyCode::Media(MediaKey::Rewind),
+    KeyCode::Media(MediaKey::TrackNext),
+    KeyCode::Media(MediaKey::TrackPrevious),
+    KeyCode::Media(MediaKey::Record),
+    KeyCode::Media(MediaKey::LowerVolume),
+    KeyCode::Media(MediaKey::RaiseVolume),
+    KeyCode::Media(MediaKey::MuteVolume),
+    KeyCode::Modifier(ModifierKey::LeftShift),
+    KeyCode::Modifier(ModifierKey::LeftControl),
+    KeyCode::Modifier(ModifierKey::LeftAlt),
+    KeyCode::Modifier(ModifierKey::LeftSuper),
+    KeyCode::Modifier(ModifierKey::LeftHyper),
+    KeyCode::Modifier(ModifierKey::LeftMeta),
+    KeyCode::Modifier(ModifierKey::RightShift),
+    KeyCode::Modifier(ModifierKey::RightControl),
+    KeyCode::Modifier(ModifierKey::RightAlt),
+    KeyCode::Modifier(ModifierKey::RightSuper),
+    KeyCode::Modifier(ModifierKey::RightHyper),
+    KeyCode::Modifier(ModifierKey::RightMeta),
+    KeyCode::Modifier(ModifierKey::IsoLevel3Shift),
+    KeyCode::Modifier(ModifierKey::IsoLevel5Shift),
+];
+
+/// Alternative names of keys, accepted when parsing.
+const KEY_ALIASES: &[(KeyCode, &str)] = &[
+    (KeyCode::Escape, "esc"),
+    (KeyCode::Enter, "return"),
+    (KeyCode::Delete, "del"),
+    (KeyCode::Insert, "ins"),
+    (KeyCode::PageUp, "pgup"),
+    (KeyCode::PageDown, "pgdn"),
+];
+
+/// Names of characters that cannot be written as themselves.
+const CHARS: &[(char, &str)] = &[(' ', "space"), ('+', "plus"), (':', "colon")];
+
+/// An error returned when parsing a key notation fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseKeyError {
+    component: String,
+}
+
+impl fmt::Display for ParseKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid key notation: unrecognized `{}`", self.component)
+    }
+}
+
+impl std::error::Error for ParseKeyError {}
+
+impl ParseKeyError {
+    fn new(component: &str) -> Self {
+        Self { component: component.to_owned() }
+    }
+}
+
+impl fmt::Display for KeyCode {
+    /// Formats the key name, following the names used by the kitty keyboard protocol.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match *self {
+            KeyCode::Char(c) => match CHARS.iter().find(|(named, _)| *named == c) {
+                Some((_, name)) => name,
+                None => return write!(f, "{c}"),
+            },
+            KeyCode::F(n) => return write!(f, "F{n}"),
+            KeyCode::Escape => "escape",
+            KeyCode::Enter => "enter",
+            KeyCode::Tab => "tab",
+            KeyCode::Backspace => "backspace",
+            KeyCode::Insert => "insert",
+            KeyCode::Delete => "delete",
+            KeyCode::Left => "left",
+            KeyCode::Right => "right",
+            KeyCode::Up => "up",
+            KeyCode::Down => "down",
+            KeyCode::PageUp => "page_up",
+            KeyCode::PageDown => "page_down",
+            KeyCode::Home => "home",
+            KeyCode::End => "end",
+            KeyCode::CapsLock => "caps_lock",
+            KeyCode::ScrollLock => "scroll_lock",
+            KeyCode::NumLock => "num_lock",
+            KeyCode::PrintScreen => "print_screen",
+            KeyCode::Pause => "pause",
+            KeyCode::Menu => "menu",
+            KeyCode::Keypad(key) => match key {
+                KeypadKey::Digit(n) => return write!(f, "kp_{n}"),
+                KeypadKey::Decimal => "kp_decimal",
+                KeypadKey::Divide => "kp_divide",
+                KeypadKey::Multiply => "kp_multiply",
+                KeypadKey::Subtract => "kp_subtract",
+                KeypadKey::Add => "kp_add",
+                KeypadKey::Enter => "kp_enter",
+                KeypadKey::Equal => "kp_equal",
+                KeypadKey::Separator => "kp_separator",
+                KeypadKey::Left => "kp_left",
+                KeypadKey::Right => "kp_right",
+                KeypadKey::Up => "kp_up",
+                KeypadKey::Down => "kp_down",
+                KeypadKey::PageUp => "kp_page_up",
+                KeypadKey::PageDown => "kp_page_down",
+                KeypadKey::Home => "kp_home",
+                KeypadKey::End => "kp_end",
+                KeypadKey::Insert => "kp_insert",
+                KeypadKey::Delete => "kp_delete",
+                KeypadKey::Begin => "kp_begin",
+            },
+            KeyCode::Media(key) => match key {
+                MediaKey::Play => "media_play",
+                MediaKey::Pause => "media_pause",
+                MediaKey::PlayPause => "media_play_pause",
+                MediaKey::Reverse => "media_reverse",
+                MediaKey::Stop => "media_stop",
+                MediaKey::FastForward => "media_fast_forward",
+                MediaKey::Rewind => "media_rewind",
+                MediaKey::TrackNext => "media_track_next",
+                MediaKey::TrackPrevious => "media_track_previous",
+                MediaKey::Record => "media_record",
+                MediaKey::LowerVolume => "lower_volume",
+                MediaKey::RaiseVolume => "raise_volume",
+                MediaKey::MuteVolume => "mute_volume",
+            },
+            KeyCode::Modifier(key) => match key {
+                ModifierKey::LeftShift => "left_shift",
+                ModifierKey::LeftControl => "left_control",
+                ModifierKey::LeftAlt => "left_alt",
+                ModifierKey::LeftSuper => "left_super",
+                ModifierKey::LeftHyper => "left_hyper",
+                ModifierKey::LeftMeta => "left_meta",
+                ModifierKey::RightShift => "right_shift",
+                ModifierKey::RightControl => "right_control",
+                ModifierKey::RightAlt => "right_alt",
+                ModifierKey::RightSuper => "right_super",
+                ModifierKey::RightHyper => "right_hyper",
+                ModifierKey::RightMeta => "right_meta",
+                ModifierKey::IsoLevel3Shift => "iso_level3_shift",
+                ModifierKey::IsoLevel5Shift => "iso_level5_shift",
+            },
+        };
+
+        f.write_str(name)
+    }
+}
+
+impl FromStr for KeyCode {
+    type Err = ParseKeyError;
+
+    /// Parses a key name.
+    ///
+    /// Names are case-insensitive, except for single characters which are always taken literally.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(KeyCode::Char(c));
+        }
+
+        let named = NAMED_KEYS.iter().find(|code| code.to_string().eq_ignore_ascii_case(s));
+        let alias = || KEY_ALIASES.iter().find(|(_, name)| name.eq_ignore_ascii_case(s));
+
+        if let Some(code) = named.or_else(|| alias().map(|(code, _)| code)) {
+            return Ok(*code);
+        }
+
+        if let Some((c, _)) = CHARS.iter().find(|(_, name)| name.eq_ignore_ascii_case(s)) {
+            return Ok(KeyCode::Char(*c));
+        }
+
+        let number = |prefix: &str| {
+            s.get(..prefix.len())
+                .filter(|p| p.eq_ignore_ascii_case(prefix))
+                .map(|_| &s[prefix.len()..])
+                .filter(|n| n.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|n| n.parse::<u8>().ok())
+        };
+
+        match (number("f"), number("kp_")) {
+            (Some(n @ 1..=35), _) => Ok(KeyCode::F(n)),
+            (_, Some(n @ 0..=9)) => Ok(KeyCode::Keypad(KeypadKey::Digit(n))),
+            _ => Err(ParseKeyError::new(s)),
+        }
+    }
+}
+
+impl fmt::Display for KeyEvent {
+    /// Formats the key in a human-readable notation, such as `ctrl+shift+a` or `alt+F5`.
+    ///
+    /// Alternate keys are appended with `:`, in the same order as the kitty keyboard protocol,
+    /// for example `shift+a:A` or `ctrl+с::c`. The event kind and associated text are not
+    /// included.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in MODIFIERS {
+            if self.modifiers.contains(*modifier) {
+                write!(f, "{name}+")?;
+            }
+        }
+
+        write!(f, "{}", self.code)?;
+
+        match (self.shifted_key, self.base_layout_key) {
+            (None, None) => {}
+            (Some(shifted), None) => write!(f, ":{shifted}")?,
+            (None, Some(base)) => write!(f, "::{base}")?,
+            (Some(shifted), Some(base)) => write!(f, ":{shifted}:{base}")?,
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for KeyEvent {
+    type Err = ParseKeyError;
+
+    /// Parses a key press from a notation such as `ctrl+shift+a` or `alt+F5`.
+    ///
+    /// See the [`Display`](fmt::Display) implementation for the full notation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // A trailing `+` is the plus key, rather than a separator.
+        let (modifiers, key) = match s.strip_suffix("++") {
+            Some(modifiers) => (Some(modifiers), "+"),
+            None if s == "+" => (None, "+"),
+            None => match s.rsplit_once('+') {
+                Some((modifiers, key)) => (Some(modifiers), key),
+                None => (None, s),
+            },
+        };
+
+        let modifiers = match modifiers {
+            Some(modifiers) => {
+                modifiers.split('+').try_fold(KeyModifiers::empty(), |acc, name| {
+                    MODIFIERS
+                        .iter()
+                        .chain(MODIFIER_ALIASES)
+                        .find(|(_, n)| n.eq_ignore_ascii_case(name))
+                        .map(|(modifier, _)| acc | *modifier)
+                        .ok_or_else(|| ParseKeyError::new(name))
+                })?
+            }
+            None => KeyModifiers::empty(),
+        };
+
+        // A lone `:` is the colon key, rather than a separator.
+        let (code, shifted_key, base_layout_key) = match key {
+            ":" => (":", None, None),
+            key => {
+                let mut keys = key.splitn(3, ':');
+                (keys.next().unwrap_or_default(), keys.next(), keys.next())
+            }
+        };
+
+        let alternate = |key: Option<&str>| match key {
+            None | Some("") => Ok(None),
+            Some(key) => key.parse().map(Some),
+        };
+
+        let code = code.parse()?;
+        let shifted_key = alternate(shifted_key)?;
+        let base_layout_key = alternate(base_layout_key)?;
+
+        Ok(KeyEvent { shifted_key, base_layout_key, ..KeyEvent::new(code, modifiers) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{KeyEventKind, MatchMode};
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    fn assert_round_trip(event: KeyEvent) {
+        let notation = event.to_string();
+        assert_eq!(notation.parse::<KeyEvent>(), Ok(event), "notation `{notation}`");
+    }
+
+    #[test]
+    fn round_trip_named_keys() {
+        for code in NAMED_KEYS {
+            assert_round_trip(key(*code, KeyModifiers::empty()));
+        }
+    }
+
+    #[test]
+    fn round_trip_numbered_keys() {
+        for n in 1..=35 {
+            assert_round_trip(key(KeyCode::F(n), KeyModifiers::empty()));
+        }
+        for n in 0..=9 {
+            assert_round_trip(key(KeyCode::Keypad(KeypadKey::Digit(n)), KeyModifiers::empty()));
+        }
+    }
+
+    #[test]
+    fn round_trip_chars() {
+        for c in ['a', 'A', '1', '+', ':', ' ', '-', 'é', 'с'] {
+            assert_round_trip(key(KeyCode::Char(c), KeyModifiers::empty()));
+            assert_round_trip(key(KeyCode::Char(c), KeyModifiers::CONTROL));
+        }
+    }
+
+    #[test]
+    fn round_trip_modifiers() {
+        for (modifier, _) in MODIFIERS {
+            assert_round_trip(key(KeyCode::Char('a'), *modifier));
+        }
+        assert_round_trip(key(KeyCode::Char('+'), KeyModifiers::all()));
+        assert_round_trip(key(KeyCode::F(5), KeyModifiers::CONTROL | KeyModifiers::SHIFT));
+    }
+
+    #[test]
+    fn round_trip_alternate_keys() {
+        let event = KeyEvent {
+            shifted_key: Some(KeyCode::Char('A')),
+            ..key(KeyCode::Char('a'), KeyModifiers::SHIFT)
+        };
+        assert_eq!(event.to_string(), "shift+a:A");
+        assert_round_trip(event);
+
+        let event = KeyEvent {
+            base_layout_key: Some(KeyCode::Char('c')),
+            ..key(KeyCode::Char('с'), KeyModifiers::CONTROL)
+        };
+        assert_eq!(event.to_string(), "ctrl+с::c");
+        assert_round_trip(event);
+
+        let event = KeyEvent {
+            shifted_key: Some(KeyCode::Char(':')),
+            base_layout_key: Some(KeyCode::Char(';')),
+            ..key(KeyCode::Char(';'), KeyModifiers::empty())
+        };
+        assert_eq!(event.to_string(), ";:colon:;");
+        assert_round_trip(event);
+    }
+
+    #[test]
+    fn parse_aliases() {
+        assert_eq!("Control+Esc".parse(), Ok(key(KeyCode::Escape, KeyModifiers::CONTROL)));
+        assert_eq!("cmd+pgup".parse(), Ok(key(KeyCode::PageUp, KeyModifiers::SUPER)));
+        assert_eq!("ctrl++".parse(), Ok(key(KeyCode::Char('+'), KeyModifiers::CONTROL)));
+        assert_eq!("+".parse(), Ok(key(KeyCode::Char('+'), KeyModifiers::empty())));
+        assert_eq!(":".parse(), Ok(key(KeyCode::Char(':'), KeyModifiers::empty())));
+        assert_eq!("F5".parse(), Ok(key(KeyCode::F(5), KeyModifiers::empty())));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!("ctrl+nope".parse::<KeyEvent>(), Err(ParseKeyError::new("nope")));
+        assert_eq!("hold+a".parse::<KeyEvent>(), Err(ParseKeyError::new("hold")));
+        assert_eq!("f+5".parse::<KeyCode>(), Err(ParseKeyError::new("f+5")));
+        assert_eq!("F0".parse::<KeyCode>(), Err(ParseKeyError::new("F0")));
+        assert_eq!("F36".parse::<KeyCode>(), Err(ParseKeyError::new("F36")));
+        assert_eq!("kp_10".parse::<KeyCode>(), Err(ParseKeyError::new("kp_10")));
+    }
+
+    #[test]
+    fn matches_press_and_repeat_only() {
+        let pattern: KeyEvent = "ctrl+c".parse().unwrap();
+
+        for (kind, expected) in [
+            (KeyEventKind::Press, true),
+            (KeyEventKind::Repeat, true),
+            (KeyEventKind::Release, false),
+        ] {
+            let event = KeyEvent { kind, ..key(KeyCode::Char('c'), KeyModifiers::CONTROL) };
+            assert_eq!(pattern.matches(&event, MatchMode::Literal), expected, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn matches_layout_aware() {
+        let pattern: KeyEvent = "shift+a".parse().unwrap();
+        let event = key(KeyCode::Char('A'), KeyModifiers::empty());
+        assert!(pattern.matches(&event, MatchMode::LayoutAware));
+        assert!(!pattern.matches(&event, MatchMode::Literal));
+
+        let pattern: KeyEvent = "ctrl+c".parse().unwrap();
+        let event = KeyEvent {
+            base_layout_key: Some(KeyCode::Char('c')),
+            ..key(KeyCode::Char('с'), KeyModifiers::CONTROL)
+        };
+        assert!(pattern.matches(&event, MatchMode::LayoutAware));
+        assert!(!pattern.matches(&event, MatchMode::Literal));
+
+        let event = key(KeyCode::Char('c'), KeyModifiers::CONTROL | KeyModifiers::CAPS_LOCK);
+        assert!(pattern.matches(&event, MatchMode::Literal));
+    }
+}
@@ -5,91 +5,900 @@ use vtparse::{CsiParam, VTActor, VTParser};
 
 use crate::event::Event;
 
-use super::{DesktopNotificationsSupport, KeyboardEnhancementFlags};
+use super::{
+    CursorPosition, CursorStyle, DesktopNotificationsSupport, DeviceFeatures, DynamicColor,
+    DynamicColorKind, GraphicsAttributeReport, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
+    KeyboardEnhancementFlags, KeypadKey, KittyGraphicsReply, Mode, ModeReport, ModeState,
+    MouseButton, MouseEvent, MouseEventKind, PaletteColor, PrimaryDeviceAttributes, RgbColor,
+    SecondaryDeviceAttributes, Setting, StatusStringReply, TermcapReply, TerminalVersion, Theme,
+    UnknownSequence, WindowSizeKind, WindowSizeReport,
+};
+
+const ESC: u8 = 0x1b;
+
+/// Marks the end of a bracketed paste.
+const PASTE_END: &[u8] = b"\x1b[201~";
 
 pub struct Parser {
     engine: VTParser,
     actor: EventVTActor,
+    /// Bytes consumed by the engine since it was last in the ground state.
+    pending: Vec<u8>,
+    /// Raw bytes being captured without passing through the engine.
+    capture: Capture,
+}
+
+/// Raw data that follows a sequence and must not be interpreted by the engine.
+enum Capture {
+    None,
+    /// The three bytes following `CSI M` in the X10 mouse encoding.
+    X10Mouse(Vec<u8>),
+    /// The contents of a bracketed paste, up to and including `CSI 201 ~`.
+    Paste(Vec<u8>),
 }
 
 impl Parser {
     pub fn new() -> Self {
-        Parser { engine: VTParser::new(), actor: EventVTActor::new() }
+        Parser {
+            engine: VTParser::new(),
+            actor: EventVTActor::new(),
+            pending: Vec::with_capacity(64),
+            capture: Capture::None,
+        }
     }
 
     pub fn advance(&mut self, buffer: &[u8]) {
-        self.engine.parse(buffer, &mut self.actor);
+        for &byte in buffer {
+            self.advance_byte(byte);
+        }
     }
 
-    pub fn next(&mut self) -> Option<Event> {
+    fn advance_byte(&mut self, byte: u8) {
+        match &mut self.capture {
+            Capture::None => {}
+            Capture::X10Mouse(bytes) => {
+                bytes.push(byte);
+                if let [cb, cx, cy] = bytes[..] {
+                    self.actor.x10_mouse(cb, cx, cy);
+                    self.capture = Capture::None;
+                }
+                return;
+            }
+            Capture::Paste(bytes) => {
+                bytes.push(byte);
+                if let Some(len) = bytes.strip_suffix(PASTE_END).map(<[u8]>::len) {
+                    bytes.truncate(len);
+                    let bytes = core::mem::take(bytes);
+                    self.actor.paste(bytes);
+                    self.capture = Capture::None;
+                }
+                return;
+            }
+        }
+
+        // An escape inside a sequence either starts a string terminator or interrupts the
+        // sequence. If it is not followed by `\`, start over from the escape so that it is
+        // handled the same as an escape received in the ground state.
+        if self.pending.len() > 1 && self.pending.last() == Some(&ESC) && byte != b'\\' {
+            self.engine = VTParser::new();
+            self.pending.clear();
+            self.pending.push(ESC);
+            self.engine.parse_byte(ESC, &mut self.actor);
+        }
+
+        // An escape followed by a byte that cannot start a sequence we care about is an
+        // Alt-prefixed key. The engine would either dispatch it as an escape sequence or, for
+        // intermediates and UTF-8, wait for more bytes, so take it out of the escape state and
+        // feed the byte in the ground state instead.
+        if self.pending == [ESC] && byte == ESC {
+            // A second escape makes the first an Alt prefix, unless it already was one.
+            if self.actor.alt {
+                self.actor.key(KeyCode::Escape, KeyModifiers::empty());
+            } else {
+                self.actor.alt = true;
+            }
+            self.engine = VTParser::new();
+            self.pending.clear();
+        } else if self.pending == [ESC] && is_alt_key_byte(byte) {
+            self.engine = VTParser::new();
+            self.pending.clear();
+            self.actor.alt = true;
+        }
+
+        self.pending.push(byte);
+        self.engine.parse_byte(byte, &mut self.actor);
+
+        if self.engine.is_ground() {
+            self.pending.clear();
+        }
+
+        if core::mem::take(&mut self.actor.dcs_hooked) {
+            self.dcs_header();
+        }
+
+        if core::mem::take(&mut self.actor.x10_mouse) {
+            self.capture = Capture::X10Mouse(Vec::with_capacity(3));
+        } else if core::mem::take(&mut self.actor.paste) {
+            self.capture = Capture::Paste(Vec::new());
+        }
+    }
+
+    /// Takes the parameters and intermediates of a device control string from the raw bytes.
+    ///
+    /// The engine only passes on integer parameters, padded with zeros and without private
+    /// markers, so they are read from the bytes consumed since the introducer instead.
+    fn dcs_header(&mut self) {
+        let Some(dcs) = &mut self.actor.dcs else {
+            return;
+        };
+
+        let header = match &self.pending[..] {
+            [ESC, b'P', header @ .., _final_byte] | [0x90, header @ .., _final_byte] => header,
+            _ => return,
+        };
+
+        let split =
+            header.iter().rposition(|byte| !matches!(byte, 0x20..=0x2f)).map_or(0, |i| i + 1);
+        let (params, intermediates) = header.split_at(split);
+
+        dcs.params = params.to_vec();
+        dcs.intermediates = intermediates.to_vec();
+    }
+
+    /// Notes that a cursor position report has been requested with `CSI 6 n`.
+    ///
+    /// The reply `CSI 1 ; column R` cannot be told apart from F3 with modifiers, so it is only
+    /// parsed as a cursor position report while one is expected. Replies with any other row, and
+    /// `DECXCPR` replies, are always parsed as cursor position reports.
+    pub fn expect_cursor_position(&mut self) {
+        self.actor.cursor_position_requests += 1;
+    }
+
+    /// Forgets any cursor position reports that are still expected.
+    ///
+    /// Call this once a request has timed out, so a later `CSI 1 ; modifiers R` is parsed as F3.
+    pub fn clear_cursor_position_expectation(&mut self) {
+        self.actor.cursor_position_requests = 0;
+    }
+
+    /// Returns `true` if the parser is holding a partial sequence that may need to be flushed.
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+            || !matches!(self.capture, Capture::None)
+            || self.actor.alt
+            || self.actor.prefix != KeyPrefix::None
+    }
+
+    /// Flushes a partially received sequence once input has gone idle.
+    ///
+    /// A lone escape cannot be told apart from the start of an escape sequence until more input
+    /// arrives, so this should be called once no input has been received for a short time. A
+    /// pending escape is emitted as an Escape key event, or as an Alt-prefixed key if it was
+    /// followed by a sequence introducer. A bracketed paste that was cut short is emitted with
+    /// the text received so far. Any other partial sequence is discarded.
+    pub fn flush(&mut self) {
+        match core::mem::replace(&mut self.capture, Capture::None) {
+            Capture::None | Capture::X10Mouse(_) => {}
+            Capture::Paste(bytes) => self.actor.paste(bytes),
+        }
+
+        match self.pending[..] {
+            [ESC] => self.actor.key(KeyCode::Escape, KeyModifiers::empty()),
+            [ESC, byte @ (b'[' | b']' | b'P' | b'X' | b'^' | b'_')] => {
+                self.actor.alt = true;
+                self.actor.key(KeyCode::Char(byte as char), KeyModifiers::empty());
+            }
+            // An escape that interrupted a partial sequence.
+            [.., ESC] => self.actor.key(KeyCode::Escape, KeyModifiers::empty()),
+            _ => {}
+        }
+
+        if !self.pending.is_empty() {
+            self.engine = VTParser::new();
+            self.pending.clear();
+        }
+
+        self.actor.flush();
+    }
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Iterator for Parser {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
         self.actor.events.pop_front()
     }
 }
 
+/// Returns `true` if `byte` following an escape should be treated as an Alt-prefixed key.
+fn is_alt_key_byte(byte: u8) -> bool {
+    match byte {
+        // CSI, OSC, DCS, SOS, PM, APC and SS3 introducers.
+        b'[' | b']' | b'P' | b'X' | b'^' | b'_' | b'O' => false,
+        // C0 controls, printable ASCII, DEL and UTF-8 lead bytes.
+        0x00..=0x1a | 0x1c..=0x7f | 0xc2..=0xf4 => true,
+        _ => false,
+    }
+}
+
+/// A prefix that changes the meaning of the following printed character.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum KeyPrefix {
+    None,
+    /// `ESC O`, used for application mode cursor, keypad and function keys.
+    Ss3,
+    /// `ESC O` followed by a modifier digit, e.g. `ESC O 5 P` for Ctrl+F1.
+    Ss3Modifiers(u8),
+    /// `ESC [ [`, used by the Linux console for F1 to F5.
+    LinuxFunctionKey,
+}
+
 struct EventVTActor {
     events: VecDeque<Event>,
+    /// Whether the next key was prefixed by an escape.
+    alt: bool,
+    prefix: KeyPrefix,
+    /// Whether an X10 mouse event is waiting for its raw bytes.
+    x10_mouse: bool,
+    /// Whether a bracketed paste has started.
+    paste: bool,
+    /// The device control string being received.
+    dcs: Option<DeviceControlString>,
+    /// Whether a device control string has started and is waiting for its header.
+    dcs_hooked: bool,
+    /// The number of cursor position reports expected.
+    cursor_position_requests: usize,
+}
+
+struct DeviceControlString {
+    /// The parameter bytes, including any private marker, e.g. `>` or `1`.
+    params: Vec<u8>,
+    intermediates: Vec<u8>,
+    final_byte: u8,
+    data: Vec<u8>,
 }
 
 impl EventVTActor {
     fn new() -> Self {
-        Self { events: VecDeque::with_capacity(32) }
+        Self {
+            events: VecDeque::with_capacity(32),
+            alt: false,
+            prefix: KeyPrefix::None,
+            x10_mouse: false,
+            paste: false,
+            dcs: None,
+            dcs_hooked: false,
+            cursor_position_requests: 0,
+        }
+    }
+
+    fn key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        self.push_key(KeyEvent::new(code, modifiers));
+    }
+
+    fn push_key(&mut self, mut event: KeyEvent) {
+        if core::mem::take(&mut self.alt) {
+            event.modifiers |= KeyModifiers::ALT;
+        }
+
+        self.events.push_back(Event::Key(event));
+    }
+
+    /// Flushes a pending key prefix after input has gone idle.
+    fn flush(&mut self) {
+        let prefix = core::mem::replace(&mut self.prefix, KeyPrefix::None);
+        self.prefix_keys(prefix);
+
+        self.alt = false;
+    }
+
+    /// Emits a prefix that did not start a key sequence as the keys it was made of.
+    fn prefix_keys(&mut self, prefix: KeyPrefix) {
+        match prefix {
+            KeyPrefix::None => {}
+            KeyPrefix::Ss3 => {
+                self.alt = true;
+                self.key(KeyCode::Char('O'), KeyModifiers::empty());
+            }
+            KeyPrefix::Ss3Modifiers(digit) => {
+                self.alt = true;
+                self.key(KeyCode::Char('O'), KeyModifiers::empty());
+                self.key(KeyCode::Char(digit as char), KeyModifiers::empty());
+            }
+            KeyPrefix::LinuxFunctionKey => {
+                self.alt = true;
+                self.key(KeyCode::Char('['), KeyModifiers::empty());
+                self.key(KeyCode::Char('['), KeyModifiers::empty());
+            }
+        }
+    }
+
+    fn prefixed_key(&mut self, prefix: KeyPrefix, c: char) {
+        let key = match (prefix, c) {
+            (KeyPrefix::Ss3, '2'..='9') => {
+                self.prefix = KeyPrefix::Ss3Modifiers(c as u8);
+                return;
+            }
+            (KeyPrefix::Ss3, c) => ss3_key_code(c).map(|code| (code, KeyModifiers::empty())),
+            (KeyPrefix::Ss3Modifiers(digit), c) => {
+                ss3_key_code(c).map(|code| (code, KeyModifiers::from_bits_truncate(digit - b'1')))
+            }
+            (KeyPrefix::LinuxFunctionKey, 'A'..='E') => {
+                Some((KeyCode::F(c as u8 - b'A' + 1), KeyModifiers::empty()))
+            }
+            _ => None,
+        };
+
+        match key {
+            Some((code, modifiers)) => self.key(code, modifiers),
+            // Not a key sequence after all, so emit the prefix the same as when input goes idle
+            // after it, followed by the character as a key of its own.
+            None => {
+                self.prefix_keys(prefix);
+                self.print(c);
+            }
+        }
+    }
+
+    fn legacy_letter_key(&mut self, control: u8, params: &[CsiParam]) -> bool {
+        // CSI 1 ; modifiers:event-type <letter>
+        let mut fields = fields(params);
+
+        match fields.next() {
+            Some([] | [CsiParam::Integer(1)]) => {}
+            _ => return false,
+        }
+
+        let (mut modifiers, kind) = match parse_modifiers(fields.next()) {
+            Some(modifiers) => modifiers,
+            None => return false,
+        };
+
+        let code = match control {
+            b'A' => KeyCode::Up,
+            b'B' => KeyCode::Down,
+            b'C' => KeyCode::Right,
+            b'D' => KeyCode::Left,
+            b'E' => KeyCode::Keypad(KeypadKey::Begin),
+            b'F' => KeyCode::End,
+            b'G' => KeyCode::Keypad(KeypadKey::Begin),
+            b'H' => KeyCode::Home,
+            b'P' => KeyCode::F(1),
+            b'Q' => KeyCode::F(2),
+            b'R' => KeyCode::F(3),
+            b'S' => KeyCode::F(4),
+            b'Z' => {
+                modifiers |= KeyModifiers::SHIFT;
+                KeyCode::Tab
+            }
+            _ => return false,
+        };
+
+        self.legacy_key(code, modifiers, kind);
+
+        true
+    }
+
+    fn legacy_tilde_key(&mut self, params: &[CsiParam]) -> bool {
+        // CSI number ; modifiers:event-type ~
+        let mut fields = fields(params);
+
+        let number = match fields.next() {
+            Some([CsiParam::Integer(number)]) => *number,
+            _ => return false,
+        };
+
+        let (modifiers, kind) = match parse_modifiers(fields.next()) {
+            Some(modifiers) => modifiers,
+            None => return false,
+        };
+
+        let code = match number {
+            // xterm modifyOtherKeys: CSI 27 ; modifiers ; code ~
+            27 => match fields.next() {
+                Some([CsiParam::Integer(code)]) => match kitty_key_code(*code) {
+                    Some(code) => code,
+                    None => return false,
+                },
+                _ => return false,
+            },
+            1 | 7 => KeyCode::Home,
+            2 => KeyCode::Insert,
+            3 => KeyCode::Delete,
+            4 | 8 => KeyCode::End,
+            5 => KeyCode::PageUp,
+            6 => KeyCode::PageDown,
+            11..=15 => KeyCode::F((number - 10) as u8),
+            17..=21 => KeyCode::F((number - 11) as u8),
+            23..=26 => KeyCode::F((number - 12) as u8),
+            28..=29 => KeyCode::F((number - 13) as u8),
+            31..=34 => KeyCode::F((number - 14) as u8),
+            _ => return false,
+        };
+
+        self.legacy_key(code, modifiers, kind);
+
+        true
+    }
+
+    fn legacy_key(&mut self, code: KeyCode, modifiers: KeyModifiers, kind: KeyEventKind) {
+        self.push_key(KeyEvent { kind, ..KeyEvent::new(code, modifiers) });
     }
 
-    fn primary_device_attributes(&mut self, _params: &[CsiParam]) {
-        self.events.push_back(Event::PrimaryDeviceAttributes);
+    fn primary_device_attributes(&mut self, params: &[CsiParam]) -> bool {
+        // CSI ? conformance-level ; feature ; ... c
+        //
+        // Some terminals leave a trailing empty field, e.g. kitty replies with `CSI ? 62 ; c`.
+        // Every query relies on this reply to detect the end of the other replies, so fields that
+        // are empty or do not parse are skipped rather than rejecting the reply.
+        let params = fields(params)
+            .filter_map(|field| match field {
+                [CsiParam::Integer(n)] => u16::try_from(*n).ok(),
+                _ => None,
+            })
+            .collect::<Vec<u16>>();
+
+        let conformance_level = params.first().copied().unwrap_or_default();
+
+        // Before the VT220, the parameters describe hardware options rather than features.
+        let features = if conformance_level >= 62 {
+            params.iter().skip(1).filter_map(|&code| DeviceFeatures::from_code(code)).collect()
+        } else {
+            DeviceFeatures::empty()
+        };
+
+        self.events.push_back(Event::PrimaryDeviceAttributes(PrimaryDeviceAttributes {
+            conformance_level,
+            features,
+            params,
+        }));
+
+        true
+    }
+
+    fn graphics_attribute(&mut self, params: &[CsiParam]) -> bool {
+        // CSI ? item ; status ; value ; ... S
+        let params = match fields(params)
+            .map(|field| match field {
+                [CsiParam::Integer(n)] => u16::try_from(*n).ok(),
+                _ => None,
+            })
+            .collect::<Option<Vec<u16>>>()
+        {
+            Some(params) => params,
+            None => return false,
+        };
+
+        let report = match params[..] {
+            [1, 0, registers] => GraphicsAttributeReport::ColorRegisters(registers),
+            [2, 0, width, height] => GraphicsAttributeReport::SixelGeometry { width, height },
+            [item, 1..=3, ..] => GraphicsAttributeReport::Error(item),
+            _ => return false,
+        };
+
+        self.events.push_back(Event::GraphicsAttribute(report));
+
+        true
     }
 
-    fn keyboard_enhancement_flags(&mut self, bits: i64) {
+    fn secondary_device_attributes(&mut self, params: &[CsiParam]) -> bool {
+        use CsiParam::*;
+
+        // CSI > terminal-type ; firmware-version ; rom-cartridge c
+        //
+        // Some terminals omit the ROM cartridge number.
+        let (terminal_type, firmware_version, rom_cartridge) = match params {
+            [Integer(pp), P(b';'), Integer(pv), P(b';'), Integer(pc)] => (*pp, *pv, *pc),
+            [Integer(pp), P(b';'), Integer(pv)] => (*pp, *pv, 0),
+            _ => return false,
+        };
+
+        let (Ok(terminal_type), Ok(firmware_version), Ok(rom_cartridge)) = (
+            u16::try_from(terminal_type),
+            u32::try_from(firmware_version),
+            u16::try_from(rom_cartridge),
+        ) else {
+            return false;
+        };
+
+        self.events.push_back(Event::SecondaryDeviceAttributes(SecondaryDeviceAttributes {
+            terminal_type,
+            firmware_version,
+            rom_cartridge,
+        }));
+
+        true
+    }
+
+    fn cursor_position(&mut self, params: &[CsiParam]) -> bool {
+        use CsiParam::*;
+
+        // CSI row ; column R
+        // CSI ? row ; column ; page R
+        let (row, column, page) = match params {
+            [Integer(row), P(b';'), Integer(column)]
+            | [P(b'?'), Integer(row), P(b';'), Integer(column)] => (*row, *column, None),
+            [P(b'?'), Integer(row), P(b';'), Integer(column), P(b';'), Integer(page)] => {
+                (*row, *column, Some(*page))
+            }
+            _ => return false,
+        };
+
+        let (Ok(row @ 1..), Ok(column @ 1..)) = (u16::try_from(row), u16::try_from(column)) else {
+            return false;
+        };
+        let page = match page.map(u16::try_from) {
+            None => None,
+            Some(Ok(page)) => Some(page),
+            Some(Err(_)) => return false,
+        };
+
+        self.cursor_position_requests = self.cursor_position_requests.saturating_sub(1);
+
+        self.events.push_back(Event::CursorPosition(CursorPosition {
+            column: column - 1,
+            row: row - 1,
+            page,
+        }));
+
+        true
+    }
+
+    fn window_size(&mut self, op: i64, height: i64, width: i64) -> bool {
+        // CSI 4 ; height ; width t
+        // CSI 6 ; height ; width t
+        // CSI 8 ; rows ; columns t
+        let kind = match op {
+            4 => WindowSizeKind::TextAreaPixels,
+            6 => WindowSizeKind::CellPixels,
+            8 => WindowSizeKind::TextAreaCells,
+            _ => return false,
+        };
+
+        let (Ok(height), Ok(width)) = (u16::try_from(height), u16::try_from(width)) else {
+            return false;
+        };
+
+        self.events.push_back(Event::WindowSize(WindowSizeReport { kind, width, height }));
+
+        true
+    }
+
+    fn mode_report(&mut self, mode: fn(u16) -> Mode, number: i64, state: i64) -> bool {
+        let (Ok(number), Some(state)) = (u16::try_from(number), ModeState::from_code(state)) else {
+            return false;
+        };
+
+        self.events.push_back(Event::ModeReport(ModeReport { mode: mode(number), state }));
+
+        true
+    }
+
+    fn keyboard_enhancement_flags(&mut self, bits: i64) -> bool {
         if bits < 0 {
-            return;
+            return false;
         }
 
         self.events.push_back(Event::KeyboardEnhancmentFlags(
             KeyboardEnhancementFlags::from_bits_truncate(bits as u8),
         ));
+
+        true
+    }
+
+    fn kitty_key(&mut self, params: &[CsiParam]) -> bool {
+        // CSI unicode-key-code:alternate-key-codes ; modifiers:event-type ; text-as-codepoints u
+        //
+        // See <https://sw.kovidgoyal.net/kitty/keyboard-protocol/#an-overview>
+        let mut fields = fields(params);
+
+        let mut codes = fields.next().map(subparams).into_iter().flatten();
+        let code = match codes.next().flatten().and_then(kitty_key_code) {
+            Some(code) => code,
+            None => return false,
+        };
+        let shifted_key = codes.next().flatten().and_then(kitty_key_code);
+        let base_layout_key = codes.next().flatten().and_then(kitty_key_code);
+
+        let (modifiers, kind) = match parse_modifiers(fields.next()) {
+            Some(modifiers) => modifiers,
+            None => return false,
+        };
+
+        let text = fields.next().and_then(|field| {
+            subparams(field)
+                .map(|c| c.and_then(|c| char::from_u32(u32::try_from(c).ok()?)))
+                .collect::<Option<String>>()
+        });
+
+        self.push_key(KeyEvent { code, modifiers, kind, shifted_key, base_layout_key, text });
+
+        true
+    }
+
+    fn sgr_mouse(&mut self, params: &[CsiParam], release: bool) -> bool {
+        use CsiParam::*;
+
+        // CSI < button ; x ; y M
+        // CSI < button ; x ; y m
+        match params {
+            [Integer(cb), P(b';'), Integer(cx), P(b';'), Integer(cy)] => {
+                self.mouse(*cb, *cx, *cy, release)
+            }
+            _ => false,
+        }
     }
 
-    fn desktop_notifications_support(&mut self, param1: &[u8], _param2: &[u8]) {
+    fn urxvt_mouse(&mut self, params: &[CsiParam]) -> bool {
+        use CsiParam::*;
+
+        // CSI button ; x ; y M
+        match params {
+            [Integer(cb), P(b';'), Integer(cx), P(b';'), Integer(cy)] => {
+                self.mouse(*cb - 32, *cx, *cy, false)
+            }
+            _ => false,
+        }
+    }
+
+    fn x10_mouse(&mut self, cb: u8, cx: u8, cy: u8) {
+        // CSI M button x y
+        //
+        // Each value is offset by 32 to make it printable.
+        //
+        // The raw bytes are not a sequence of their own, so an invalid event is dropped.
+        self.mouse(i64::from(cb) - 32, i64::from(cx) - 32, i64::from(cy) - 32, false);
+    }
+
+    /// Decodes a mouse event from a button code and one-based position.
+    ///
+    /// See <https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h2-Mouse-Tracking>
+    fn mouse(&mut self, cb: i64, cx: i64, cy: i64, release: bool) -> bool {
+        if !(0..=255).contains(&cb) {
+            return false;
+        }
+
+        let mut modifiers = KeyModifiers::empty();
+        if cb & 4 != 0 {
+            modifiers |= KeyModifiers::SHIFT;
+        }
+        if cb & 8 != 0 {
+            modifiers |= KeyModifiers::ALT;
+        }
+        if cb & 16 != 0 {
+            modifiers |= KeyModifiers::CONTROL;
+        }
+
+        let button = match (cb & 0b1100_0000, cb & 0b11) {
+            (0, 0) => Some(MouseButton::Left),
+            (0, 1) => Some(MouseButton::Middle),
+            (0, 2) => Some(MouseButton::Right),
+            (0, 3) => None,
+            (128, 0) => Some(MouseButton::Back),
+            (128, 1) => Some(MouseButton::Forward),
+            _ => None,
+        };
+        let motion = cb & 32 != 0;
+
+        let kind = match (cb & 0b1100_0000, cb & 0b11) {
+            (64, 0) => MouseEventKind::ScrollUp,
+            (64, 1) => MouseEventKind::ScrollDown,
+            (64, 2) => MouseEventKind::ScrollLeft,
+            (64, 3) => MouseEventKind::ScrollRight,
+            (0, 3) if motion => MouseEventKind::Moved,
+            // Legacy encodings report a release without the button.
+            (0, 3) => MouseEventKind::Up(MouseButton::Left),
+            _ => match button {
+                Some(button) if motion => MouseEventKind::Drag(button),
+                Some(button) if release => MouseEventKind::Up(button),
+                Some(button) => MouseEventKind::Down(button),
+                None => return false,
+            },
+        };
+
+        self.mouse_event(kind, cx, cy, modifiers);
+
+        true
+    }
+
+    fn mouse_event(&mut self, kind: MouseEventKind, cx: i64, cy: i64, modifiers: KeyModifiers) {
+        let position = |n: i64| n.saturating_sub(1).clamp(0, i64::from(u16::MAX)) as u16;
+
+        self.events.push_back(Event::Mouse(MouseEvent {
+            kind,
+            column: position(cx),
+            row: position(cy),
+            modifiers,
+        }));
+    }
+
+    fn paste(&mut self, bytes: Vec<u8>) {
+        let event = match String::from_utf8(bytes) {
+            Ok(text) => Event::Paste(text),
+            Err(err) => Event::InvalidPaste(err.into_bytes()),
+        };
+        self.events.push_back(event);
+    }
+
+    fn unknown_csi(&mut self, params: &[CsiParam], control: u8) {
+        // The engine promotes intermediates to parameters, so split them back off the end.
+        let split = params
+            .iter()
+            .rposition(|param| !matches!(param, CsiParam::P(0x20..=0x2f)))
+            .map_or(0, |i| i + 1);
+        let (params, intermediates) = params.split_at(split);
+
+        self.events.push_back(Event::Unknown(UnknownSequence::Csi {
+            params: params.iter().map(|param| param.to_string()).collect::<String>().into_bytes(),
+            intermediates: intermediates
+                .iter()
+                .filter_map(|param| match param {
+                    CsiParam::P(byte) => Some(*byte),
+                    CsiParam::Integer(_) => None,
+                })
+                .collect(),
+            final_byte: control,
+        }));
+    }
+
+    fn dcs(&mut self, dcs: DeviceControlString) {
+        let DeviceControlString { params, intermediates, final_byte, data } = dcs;
+
+        let event = match (&params[..], &intermediates[..], final_byte) {
+            // DCS > | name(version) ST
+            (b">", b"", b'|') => terminal_version(&data).map(Event::TerminalVersion),
+            // DCS 1 + r name=value ; ... ST
+            (b"1", b"+", b'r') => {
+                termcap_capabilities(&data).map(|caps| Event::Termcap(TermcapReply::Valid(caps)))
+            }
+            // DCS 0 + r name ST
+            (b"0", b"+", b'r') => Some(Event::Termcap(TermcapReply::Invalid)),
+            // DCS Ps $ r Pt ST
+            //
+            // xterm replies with 1 for a valid request, while DEC documents 0, so a reply is
+            // valid whenever it contains a setting.
+            (b"0" | b"1", b"$", b'r') => match setting(&data) {
+                Some(setting) => Some(Event::StatusString(StatusStringReply::Valid(setting))),
+                None if data.is_empty() => Some(Event::StatusString(StatusStringReply::Invalid)),
+                None => None,
+            },
+            _ => None,
+        };
+
+        match event {
+            Some(event) => self.events.push_back(event),
+            None => self.events.push_back(Event::Unknown(UnknownSequence::Dcs {
+                params,
+                intermediates,
+                final_byte,
+                data,
+            })),
+        }
+    }
+
+    fn dynamic_color(&mut self, kind: DynamicColorKind, spec: &[u8]) -> bool {
+        let Some(color) = RgbColor::parse(spec) else {
+            return false;
+        };
+
+        self.events.push_back(Event::DynamicColor(DynamicColor { kind, color }));
+
+        true
+    }
+
+    fn palette_color(&mut self, index: &[u8], spec: &[u8]) -> bool {
+        let (Some(index), Some(color)) = (
+            str::from_utf8(index).ok().and_then(|index| index.parse().ok()),
+            RgbColor::parse(spec),
+        ) else {
+            return false;
+        };
+
+        self.events.push_back(Event::PaletteColor(PaletteColor { index, color }));
+
+        true
+    }
+
+    fn desktop_notifications_support(&mut self, param1: &[u8], _param2: &[u8]) -> bool {
         let identifier = match param1
             .strip_prefix(b"i=")
             .and_then(|s| s.strip_suffix(b":p=?"))
             .and_then(|s| str::from_utf8(s).ok())
         {
             Some(identifier) => identifier,
-            None => return,
+            None => return false,
         };
 
         self.events.push_back(Event::DesktopNotifications(DesktopNotificationsSupport {
             identifier: identifier.to_owned(),
         }));
+
+        true
     }
 }
 
 impl VTActor for EventVTActor {
-    fn print(&mut self, _b: char) {}
+    fn print(&mut self, c: char) {
+        let prefix = core::mem::replace(&mut self.prefix, KeyPrefix::None);
+        if prefix != KeyPrefix::None {
+            self.prefixed_key(prefix, c);
+            return;
+        }
 
-    fn execute_c0_or_c1(&mut self, _control: u8) {}
+        match c {
+            '\x7f' => self.key(KeyCode::Backspace, KeyModifiers::empty()),
+            c => self.key(KeyCode::Char(c), KeyModifiers::empty()),
+        }
+    }
+
+    fn execute_c0_or_c1(&mut self, control: u8) {
+        self.prefix = KeyPrefix::None;
+
+        let (code, modifiers) = match control {
+            b'\r' | b'\n' => (KeyCode::Enter, KeyModifiers::empty()),
+            b'\t' => (KeyCode::Tab, KeyModifiers::empty()),
+            0x08 => (KeyCode::Backspace, KeyModifiers::empty()),
+            0x00 => (KeyCode::Char(' '), KeyModifiers::CONTROL),
+            0x01..=0x1a => (KeyCode::Char((control - 0x01 + b'a') as char), KeyModifiers::CONTROL),
+            0x1c..=0x1f => (KeyCode::Char((control - 0x1c + b'\\') as char), KeyModifiers::CONTROL),
+            // C1 controls are never produced by a key press.
+            _ => {
+                self.alt = false;
+                return;
+            }
+        };
+
+        self.key(code, modifiers);
+    }
 
     fn dcs_hook(
         &mut self,
-        _mode: u8,
+        mode: u8,
         _params: &[i64],
         _intermediates: &[u8],
         _ignored_excess_intermediates: bool,
     ) {
+        // The parameters and intermediates are filled in by `Parser::dcs_header`.
+        self.dcs = Some(DeviceControlString {
+            params: Vec::new(),
+            intermediates: Vec::new(),
+            final_byte: mode,
+            data: Vec::new(),
+        });
+        self.dcs_hooked = true;
     }
 
-    fn dcs_put(&mut self, _byte: u8) {}
+    fn dcs_put(&mut self, byte: u8) {
+        if let Some(dcs) = &mut self.dcs {
+            dcs.data.push(byte);
+        }
+    }
 
-    fn dcs_unhook(&mut self) {}
+    fn dcs_unhook(&mut self) {
+        if let Some(dcs) = self.dcs.take() {
+            self.dcs(dcs);
+        }
+    }
 
     fn esc_dispatch(
         &mut self,
         _params: &[i64],
-        _intermediates: &[u8],
+        intermediates: &[u8],
         _ignored_excess_intermediates: bool,
-        _byte: u8,
+        byte: u8,
     ) {
+        // Any other byte following an escape is taken as an Alt-prefixed key before it reaches
+        // the engine, see `Parser::advance_byte`. The only other escape sequence is the string
+        // terminator, `ESC \\`, and the string itself has already been dispatched.
+        if let ([], b'O') = (intermediates, byte) {
+            self.prefix = KeyPrefix::Ss3;
+        }
     }
 
     fn csi_dispatch(&mut self, params: &[CsiParam], _parameters_truncated: bool, control: u8) {
@@ -97,25 +906,1013 @@ impl VTActor for EventVTActor {
 
         // print!("csi: params={params:?} control={control}\r\n");
 
-        match (control, params) {
+        self.prefix = KeyPrefix::None;
+
+        // Handlers return `false` if they reject the parameters.
+        let handled = match (control, params) {
             (b'c', [P(b'?'), params @ ..]) => self.primary_device_attributes(params),
+            (b'c', [P(b'>'), params @ ..]) => self.secondary_device_attributes(params),
+            (b'y', [P(b'?'), Integer(mode), P(b';'), Integer(state), P(b'$')]) => {
+                self.mode_report(Mode::Dec, *mode, *state)
+            }
+            (b'y', [Integer(mode), P(b';'), Integer(state), P(b'$')]) => {
+                self.mode_report(Mode::Ansi, *mode, *state)
+            }
+            (
+                b't',
+                [Integer(op @ (4 | 6 | 8)), P(b';'), Integer(height), P(b';'), Integer(width)],
+            ) => self.window_size(*op, *height, *width),
+            (b'n', [P(b'?'), Integer(997), P(b';'), Integer(1)]) => {
+                self.events.push_back(Event::ThemeChanged(Theme::Dark));
+                true
+            }
+            (b'n', [P(b'?'), Integer(997), P(b';'), Integer(2)]) => {
+                self.events.push_back(Event::ThemeChanged(Theme::Light));
+                true
+            }
             (b'u', [P(b'?'), Integer(bits)]) => self.keyboard_enhancement_flags(*bits),
-            _ => {
-                // TODO: Add more.
+            (b'u', [Integer(_), ..]) => self.kitty_key(params),
+            (b'~', [Integer(200)]) => {
+                self.paste = true;
+                true
+            }
+            (b'~', [Integer(_), ..]) => self.legacy_tilde_key(params),
+            (b'R', [P(b'?'), ..]) => self.cursor_position(params),
+            (b'R', [Integer(row), P(b';'), Integer(_)])
+                if *row != 1 || self.cursor_position_requests > 0 =>
+            {
+                self.cursor_position(params)
+            }
+            (b'S', [P(b'?'), params @ ..]) => self.graphics_attribute(params),
+            (b'A'..=b'H' | b'P'..=b'S' | b'Z', [] | [Integer(_), ..]) => {
+                self.legacy_letter_key(control, params)
+            }
+            (b'[', []) => {
+                self.prefix = KeyPrefix::LinuxFunctionKey;
+                true
+            }
+            (b'M', [P(b'<'), params @ ..]) => self.sgr_mouse(params, false),
+            (b'm', [P(b'<'), params @ ..]) => self.sgr_mouse(params, true),
+            (b'M', [Integer(_), ..]) => self.urxvt_mouse(params),
+            (b'M', []) => {
+                self.x10_mouse = true;
+                true
+            }
+            (b'I', []) => {
+                self.events.push_back(Event::FocusGained);
+                true
             }
+            (b'O', []) => {
+                self.events.push_back(Event::FocusLost);
+                true
+            }
+            _ => false,
+        };
+
+        if !handled {
+            self.unknown_csi(params, control);
+        }
+
+        // An escape prefix only applies to a key sequence.
+        if self.prefix == KeyPrefix::None {
+            self.alt = false;
         }
     }
 
     fn osc_dispatch(&mut self, params: &[&[u8]]) {
         // print!("osc: {params:?}\r\n");
 
-        match params {
+        // Handlers return `false` if they reject the parameters.
+        let handled = match params {
+            [b"4", index, spec] => self.palette_color(index, spec),
+            [b"10", spec] => self.dynamic_color(DynamicColorKind::Foreground, spec),
+            [b"11", spec] => self.dynamic_color(DynamicColorKind::Background, spec),
+            [b"12", spec] => self.dynamic_color(DynamicColorKind::Cursor, spec),
             [[b'9', b'9'], param1, param2] => self.desktop_notifications_support(param1, param2),
-            _ => {
-                // TODO: Add more.
+            _ => false,
+        };
+
+        if !handled {
+            self.events.push_back(Event::Unknown(UnknownSequence::Osc {
+                params: params.iter().map(|param| param.to_vec()).collect(),
+            }));
+        }
+    }
+
+    fn apc_dispatch(&mut self, data: Vec<u8>) {
+        match kitty_graphics_reply(&data) {
+            Some(reply) => self.events.push_back(Event::KittyGraphics(reply)),
+            None => self.events.push_back(Event::Unknown(UnknownSequence::Apc { data })),
+        }
+    }
+}
+
+/// Splits CSI parameters into `;` separated fields.
+fn fields(params: &[CsiParam]) -> impl Iterator<Item = &[CsiParam]> {
+    params.split(|param| *param == CsiParam::P(b';'))
+}
+
+/// Splits a CSI parameter field into `:` separated sub-parameters.
+///
+/// Omitted sub-parameters are returned as `None`.
+fn subparams(field: &[CsiParam]) -> impl Iterator<Item = Option<i64>> + '_ {
+    field.split(|param| *param == CsiParam::P(b':')).map(|subparam| match subparam {
+        [CsiParam::Integer(n)] => Some(*n),
+        _ => None,
+    })
+}
+
+/// Converts the final character of an SS3 sequence into a key code.
+fn ss3_key_code(c: char) -> Option<KeyCode> {
+    let code = match c {
+        'A' => KeyCode::Up,
+        'B' => KeyCode::Down,
+        'C' => KeyCode::Right,
+        'D' => KeyCode::Left,
+        'E' => KeyCode::Keypad(KeypadKey::Begin),
+        'F' => KeyCode::End,
+        'H' => KeyCode::Home,
+        'P' => KeyCode::F(1),
+        'Q' => KeyCode::F(2),
+        'R' => KeyCode::F(3),
+        'S' => KeyCode::F(4),
+        // Application keypad mode.
+        'M' => KeyCode::Keypad(KeypadKey::Enter),
+        'X' => KeyCode::Keypad(KeypadKey::Equal),
+        'j' => KeyCode::Keypad(KeypadKey::Multiply),
+        'k' => KeyCode::Keypad(KeypadKey::Add),
+        'l' => KeyCode::Keypad(KeypadKey::Separator),
+        'm' => KeyCode::Keypad(KeypadKey::Subtract),
+        'n' => KeyCode::Keypad(KeypadKey::Decimal),
+        'o' => KeyCode::Keypad(KeypadKey::Divide),
+        'p'..='y' => KeyCode::Keypad(KeypadKey::Digit(c as u8 - b'p')),
+        _ => return None,
+    };
+    Some(code)
+}
+
+fn kitty_key_code(number: i64) -> Option<KeyCode> {
+    KeyCode::from_kitty_number(u32::try_from(number).ok()?)
+}
+
+/// Parses a `modifiers:event-type` field, as used by both the kitty keyboard protocol and the
+/// legacy xterm modifier encoding.
+fn parse_modifiers(field: Option<&[CsiParam]>) -> Option<(KeyModifiers, KeyEventKind)> {
+    let mut subparams = field.map(subparams).into_iter().flatten();
+
+    let modifiers = match subparams.next().flatten() {
+        None => KeyModifiers::empty(),
+        Some(n @ 1..=256) => KeyModifiers::from_bits_truncate((n - 1) as u8),
+        Some(_) => return None,
+    };
+
+    let kind = match subparams.next().flatten() {
+        None | Some(1) => KeyEventKind::Press,
+        Some(2) => KeyEventKind::Repeat,
+        Some(3) => KeyEventKind::Release,
+        Some(_) => return None,
+    };
+
+    Some((modifiers, kind))
+}
+
+/// Parses the data of a kitty graphics protocol reply, `APC G control-data ; message ST`.
+fn kitty_graphics_reply(data: &[u8]) -> Option<KittyGraphicsReply> {
+    let (control, message) = str::from_utf8(data.strip_prefix(b"G")?).ok()?.split_once(';')?;
+
+    let image_id = control
+        .split(',')
+        .find_map(|key_value| key_value.strip_prefix("i="))
+        .map(|id| id.parse().ok())
+        .unwrap_or(None);
+
+    Some(KittyGraphicsReply { image_id, message: message.to_owned() })
+}
+
+/// Parses the data of a `DECRQSS` reply, the parameters and control function of a setting.
+fn setting(data: &[u8]) -> Option<Setting> {
+    let data = str::from_utf8(data).ok()?;
+
+    let numbers = |params: &str| -> Option<Vec<u16>> {
+        params.split(';').map(|param| param.parse().ok()).collect()
+    };
+
+    if let Some(params) = data.strip_suffix(" q") {
+        let code = if params.is_empty() { 0 } else { params.parse().ok()? };
+        return CursorStyle::from_code(code).map(Setting::CursorStyle);
+    }
+
+    if let Some(params) = data.strip_suffix("\"p") {
+        return match numbers(params)?[..] {
+            [level] => Some(Setting::ConformanceLevel { level, eight_bit_controls: None }),
+            [level, controls] => {
+                Some(Setting::ConformanceLevel { level, eight_bit_controls: Some(controls != 1) })
             }
+            _ => None,
+        };
+    }
+
+    if let Some(params) = data.strip_suffix('m') {
+        let valid = params.bytes().all(|b| matches!(b, b'0'..=b'9' | b';' | b':'));
+        return valid.then(|| Setting::Sgr(params.to_owned()));
+    }
+
+    if let Some(params) = data.strip_suffix('r') {
+        return match numbers(params)?[..] {
+            [top @ 1..=u16::MAX, bottom @ 1..=u16::MAX] => {
+                Some(Setting::ScrollMargins { top: top - 1, bottom: bottom - 1 })
+            }
+            _ => None,
+        };
+    }
+
+    if let Some(params) = data.strip_suffix('s') {
+        return match numbers(params)?[..] {
+            [left @ 1..=u16::MAX, right @ 1..=u16::MAX] => {
+                Some(Setting::LeftRightMargins { left: left - 1, right: right - 1 })
+            }
+            _ => None,
+        };
+    }
+
+    None
+}
+
+/// Parses the data of a valid `XTGETTCAP` reply, `DCS 1 + r name=value ; ... ST`.
+///
+/// Names and values are hex encoded. Boolean capabilities are reported without a value.
+fn termcap_capabilities(data: &[u8]) -> Option<Vec<(String, Option<String>)>> {
+    let decode = |hex: &[u8]| {
+        let bytes = hex
+            .chunks(2)
+            .map(|pair| match pair {
+                [hi, lo] => {
+                    Some(((*hi as char).to_digit(16)? << 4 | (*lo as char).to_digit(16)?) as u8)
+                }
+                _ => None,
+            })
+            .collect::<Option<Vec<u8>>>()?;
+        String::from_utf8(bytes).ok()
+    };
+
+    data.split(|&b| b == b';')
+        .map(|capability| match capability.iter().position(|&b| b == b'=') {
+            Some(i) => Some((decode(&capability[..i])?, Some(decode(&capability[i + 1..])?))),
+            None => Some((decode(capability)?, None)),
+        })
+        .collect()
+}
+
+/// Parses the data of an `XTVERSION` reply, `DCS > | name(version) ST`.
+fn terminal_version(data: &[u8]) -> Option<TerminalVersion> {
+    let data = str::from_utf8(data).ok()?.trim();
+    if data.is_empty() {
+        return None;
+    }
+
+    let (name, version) = match data.strip_suffix(')').and_then(|s| s.split_once('(')) {
+        Some((name, version)) => (name.trim_end(), Some(version)),
+        None => match data.split_once(' ') {
+            Some((name, version)) => (name, Some(version.trim_start())),
+            None => (data, None),
+        },
+    };
+
+    Some(TerminalVersion {
+        name: name.to_owned(),
+        version: version.filter(|v| !v.is_empty()).map(str::to_owned),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::ModifierKey;
+
+    /// Parses `input` as if it was followed by a pause in input.
+    fn parse(input: &[u8]) -> Vec<Event> {
+        let mut parser = Parser::new();
+        parser.advance(input);
+        parser.flush();
+        parser.collect()
+    }
+
+    fn parse_one(input: &[u8]) -> Event {
+        match &parse(input)[..] {
+            [event] => event.clone(),
+            events => panic!("expected one event for {input:?}, got {events:?}"),
         }
     }
 
-    fn apc_dispatch(&mut self, _data: Vec<u8>) {}
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn kitty_keys() {
+        assert_eq!(parse_one(b"\x1b[97u"), key(KeyCode::Char('a'), KeyModifiers::empty()));
+        assert_eq!(parse_one(b"\x1b[97;5u"), key(KeyCode::Char('a'), KeyModifiers::CONTROL));
+        assert_eq!(parse_one(b"\x1b[97;1u"), key(KeyCode::Char('a'), KeyModifiers::empty()));
+        assert_eq!(parse_one(b"\x1b[27u"), key(KeyCode::Escape, KeyModifiers::empty()));
+        assert_eq!(parse_one(b"\x1b[13;3u"), key(KeyCode::Enter, KeyModifiers::ALT));
+        assert_eq!(
+            parse_one(b"\x1b[57441;2u"),
+            key(KeyCode::Modifier(ModifierKey::LeftShift), KeyModifiers::SHIFT)
+        );
+    }
+
+    #[test]
+    fn kitty_lock_modifiers() {
+        assert_eq!(parse_one(b"\x1b[97;65u"), key(KeyCode::Char('a'), KeyModifiers::CAPS_LOCK));
+        assert_eq!(
+            parse_one(b"\x1b[97;133u"),
+            key(KeyCode::Char('a'), KeyModifiers::NUM_LOCK | KeyModifiers::CONTROL)
+        );
+    }
+
+    #[test]
+    fn kitty_event_types() {
+        for (input, kind) in [
+            (&b"\x1b[97;5:1u"[..], KeyEventKind::Press),
+            (b"\x1b[97;5:2u", KeyEventKind::Repeat),
+            (b"\x1b[97;5:3u", KeyEventKind::Release),
+        ] {
+            assert_eq!(
+                parse_one(input),
+                Event::Key(KeyEvent {
+                    kind,
+                    ..KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL)
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn kitty_alternate_keys() {
+        assert_eq!(
+            parse_one(b"\x1b[97:65;2u"),
+            Event::Key(KeyEvent {
+                shifted_key: Some(KeyCode::Char('A')),
+                ..KeyEvent::new(KeyCode::Char('a'), KeyModifiers::SHIFT)
+            })
+        );
+        assert_eq!(
+            parse_one(b"\x1b[1089::99;5u"),
+            Event::Key(KeyEvent {
+                base_layout_key: Some(KeyCode::Char('c')),
+                ..KeyEvent::new(KeyCode::Char('с'), KeyModifiers::CONTROL)
+            })
+        );
+        assert_eq!(
+            parse_one(b"\x1b[1089:1057:99;2u"),
+            Event::Key(KeyEvent {
+                shifted_key: Some(KeyCode::Char('С')),
+                base_layout_key: Some(KeyCode::Char('c')),
+                ..KeyEvent::new(KeyCode::Char('с'), KeyModifiers::SHIFT)
+            })
+        );
+    }
+
+    #[test]
+    fn kitty_associated_text() {
+        assert_eq!(
+            parse_one(b"\x1b[97;2;65u"),
+            Event::Key(KeyEvent {
+                text: Some("A".to_owned()),
+                ..KeyEvent::new(KeyCode::Char('a'), KeyModifiers::SHIFT)
+            })
+        );
+        assert_eq!(
+            parse_one(b"\x1b[97;;104:105u"),
+            Event::Key(KeyEvent {
+                text: Some("hi".to_owned()),
+                ..KeyEvent::new(KeyCode::Char('a'), KeyModifiers::empty())
+            })
+        );
+    }
+
+    #[test]
+    fn kitty_invalid_keys() {
+        assert!(matches!(parse_one(b"\x1b[97;999u"), Event::Unknown(UnknownSequence::Csi { .. })));
+        assert!(matches!(parse_one(b"\x1b[97;5:9u"), Event::Unknown(UnknownSequence::Csi { .. })));
+    }
+
+    #[test]
+    fn alt_keys() {
+        assert_eq!(parse_one(b"\x1ba"), key(KeyCode::Char('a'), KeyModifiers::ALT));
+        assert_eq!(parse_one(b"\x1b[1;3A"), key(KeyCode::Up, KeyModifiers::ALT));
+    }
+
+    #[test]
+    fn legacy_letter_keys() {
+        assert_eq!(parse_one(b"\x1b[A"), key(KeyCode::Up, KeyModifiers::empty()));
+        assert_eq!(parse_one(b"\x1b[D"), key(KeyCode::Left, KeyModifiers::empty()));
+        assert_eq!(parse_one(b"\x1b[1;5A"), key(KeyCode::Up, KeyModifiers::CONTROL));
+        assert_eq!(parse_one(b"\x1b[1;3H"), key(KeyCode::Home, KeyModifiers::ALT));
+        assert_eq!(parse_one(b"\x1b[F"), key(KeyCode::End, KeyModifiers::empty()));
+        assert_eq!(parse_one(b"\x1b[Z"), key(KeyCode::Tab, KeyModifiers::SHIFT));
+        assert_eq!(parse_one(b"\x1b[1;2P"), key(KeyCode::F(1), KeyModifiers::SHIFT));
+        assert_eq!(
+            parse_one(b"\x1b[E"),
+            key(KeyCode::Keypad(KeypadKey::Begin), KeyModifiers::empty())
+        );
+        assert_eq!(
+            parse_one(b"\x1b[G"),
+            key(KeyCode::Keypad(KeypadKey::Begin), KeyModifiers::empty())
+        );
+    }
+
+    #[test]
+    fn legacy_tilde_keys() {
+        assert_eq!(parse_one(b"\x1b[2~"), key(KeyCode::Insert, KeyModifiers::empty()));
+        assert_eq!(parse_one(b"\x1b[3;5~"), key(KeyCode::Delete, KeyModifiers::CONTROL));
+        assert_eq!(parse_one(b"\x1b[5~"), key(KeyCode::PageUp, KeyModifiers::empty()));
+        assert_eq!(parse_one(b"\x1b[6~"), key(KeyCode::PageDown, KeyModifiers::empty()));
+        assert_eq!(parse_one(b"\x1b[1~"), key(KeyCode::Home, KeyModifiers::empty()));
+        assert_eq!(parse_one(b"\x1b[15;2~"), key(KeyCode::F(5), KeyModifiers::SHIFT));
+        assert_eq!(parse_one(b"\x1b[24~"), key(KeyCode::F(12), KeyModifiers::empty()));
+    }
+
+    #[test]
+    fn ss3_keys() {
+        assert_eq!(parse_one(b"\x1bOA"), key(KeyCode::Up, KeyModifiers::empty()));
+        assert_eq!(parse_one(b"\x1bOP"), key(KeyCode::F(1), KeyModifiers::empty()));
+        assert_eq!(parse_one(b"\x1bO5P"), key(KeyCode::F(1), KeyModifiers::CONTROL));
+        assert_eq!(parse_one(b"\x1bO2A"), key(KeyCode::Up, KeyModifiers::SHIFT));
+        assert_eq!(
+            parse_one(b"\x1bOM"),
+            key(KeyCode::Keypad(KeypadKey::Enter), KeyModifiers::empty())
+        );
+        assert_eq!(
+            parse_one(b"\x1bOq"),
+            key(KeyCode::Keypad(KeypadKey::Digit(1)), KeyModifiers::empty())
+        );
+    }
+
+    #[test]
+    fn linux_function_keys() {
+        assert_eq!(parse_one(b"\x1b[[A"), key(KeyCode::F(1), KeyModifiers::empty()));
+        assert_eq!(parse_one(b"\x1b[[E"), key(KeyCode::F(5), KeyModifiers::empty()));
+    }
+
+    #[test]
+    fn keyboard_enhancement_flags() {
+        assert_eq!(
+            parse_one(b"\x1b[?1u"),
+            Event::KeyboardEnhancmentFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+        );
+        assert_eq!(
+            parse_one(b"\x1b[?31u"),
+            Event::KeyboardEnhancmentFlags(KeyboardEnhancementFlags::all())
+        );
+    }
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> Event {
+        Event::Mouse(MouseEvent { kind, column, row, modifiers: KeyModifiers::empty() })
+    }
+
+    #[test]
+    fn sgr_mouse() {
+        assert_eq!(
+            parse_one(b"\x1b[<0;10;20M"),
+            mouse(MouseEventKind::Down(MouseButton::Left), 9, 19)
+        );
+        assert_eq!(
+            parse_one(b"\x1b[<0;10;20m"),
+            mouse(MouseEventKind::Up(MouseButton::Left), 9, 19)
+        );
+        assert_eq!(
+            parse_one(b"\x1b[<34;1;1M"),
+            mouse(MouseEventKind::Drag(MouseButton::Right), 0, 0)
+        );
+        assert_eq!(parse_one(b"\x1b[<35;5;5M"), mouse(MouseEventKind::Moved, 4, 4));
+        assert_eq!(parse_one(b"\x1b[<64;1;1M"), mouse(MouseEventKind::ScrollUp, 0, 0));
+        assert_eq!(parse_one(b"\x1b[<65;1;1M"), mouse(MouseEventKind::ScrollDown, 0, 0));
+        assert_eq!(
+            parse_one(b"\x1b[<16;1;1M"),
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column: 0,
+                row: 0,
+                modifiers: KeyModifiers::CONTROL,
+            })
+        );
+    }
+
+    #[test]
+    fn urxvt_mouse() {
+        assert_eq!(
+            parse_one(b"\x1b[32;10;20M"),
+            mouse(MouseEventKind::Down(MouseButton::Left), 9, 19)
+        );
+    }
+
+    #[test]
+    fn x10_mouse() {
+        assert_eq!(parse_one(b"\x1b[M !!"), mouse(MouseEventKind::Down(MouseButton::Left), 0, 0));
+        assert_eq!(parse_one(b"\x1b[M`*+"), mouse(MouseEventKind::ScrollUp, 9, 10));
+    }
+
+    #[test]
+    fn paste() {
+        assert_eq!(parse_one(b"\x1b[200~hello\x1b[201~"), Event::Paste("hello".to_owned()));
+        assert_eq!(parse_one(b"\x1b[200~\x1b[201~"), Event::Paste(String::new()));
+        assert_eq!(parse_one(b"\x1b[200~a\x1b[Ab\x1b[201~"), Event::Paste("a\x1b[Ab".to_owned()));
+        assert_eq!(parse_one(b"\x1b[200~\xff\x1b[201~"), Event::InvalidPaste(vec![0xff]));
+    }
+
+    #[test]
+    fn paste_truncated() {
+        assert_eq!(parse_one(b"\x1b[200~hel"), Event::Paste("hel".to_owned()));
+
+        let mut parser = Parser::new();
+        parser.advance(b"\x1b[200~hel");
+        assert!(parser.is_pending());
+        parser.flush();
+        parser.advance(b"a");
+        assert_eq!(
+            parser.collect::<Vec<_>>(),
+            [Event::Paste("hel".to_owned()), key(KeyCode::Char('a'), KeyModifiers::empty())]
+        );
+    }
+
+    #[test]
+    fn paste_split() {
+        let mut parser = Parser::new();
+        parser.advance(b"\x1b[200~hel");
+        parser.advance(b"lo\x1b[20");
+        assert_eq!(parser.next(), None);
+
+        parser.advance(b"1~\x1b[A");
+        assert_eq!(
+            parser.collect::<Vec<_>>(),
+            [Event::Paste("hello".to_owned()), key(KeyCode::Up, KeyModifiers::empty())]
+        );
+    }
+
+    #[test]
+    fn focus() {
+        assert_eq!(parse_one(b"\x1b[I"), Event::FocusGained);
+        assert_eq!(parse_one(b"\x1b[O"), Event::FocusLost);
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(
+            parse("aé1 \x7f".as_bytes()),
+            [
+                key(KeyCode::Char('a'), KeyModifiers::empty()),
+                key(KeyCode::Char('é'), KeyModifiers::empty()),
+                key(KeyCode::Char('1'), KeyModifiers::empty()),
+                key(KeyCode::Char(' '), KeyModifiers::empty()),
+                key(KeyCode::Backspace, KeyModifiers::empty()),
+            ]
+        );
+    }
+
+    #[test]
+    fn control_keys() {
+        assert_eq!(
+            parse(b"\r\n\t\x08"),
+            [
+                key(KeyCode::Enter, KeyModifiers::empty()),
+                key(KeyCode::Enter, KeyModifiers::empty()),
+                key(KeyCode::Tab, KeyModifiers::empty()),
+                key(KeyCode::Backspace, KeyModifiers::empty()),
+            ]
+        );
+        assert_eq!(parse_one(b"\x00"), key(KeyCode::Char(' '), KeyModifiers::CONTROL));
+        assert_eq!(parse_one(b"\x01"), key(KeyCode::Char('a'), KeyModifiers::CONTROL));
+        assert_eq!(parse_one(b"\x1a"), key(KeyCode::Char('z'), KeyModifiers::CONTROL));
+        assert_eq!(parse_one(b"\x1c"), key(KeyCode::Char('\\'), KeyModifiers::CONTROL));
+        assert_eq!(
+            parse_one(b"\x1b\x01"),
+            key(KeyCode::Char('a'), KeyModifiers::CONTROL | KeyModifiers::ALT)
+        );
+    }
+
+    #[test]
+    fn escape() {
+        assert_eq!(parse_one(b"\x1b"), key(KeyCode::Escape, KeyModifiers::empty()));
+        assert_eq!(parse_one(b"\x1b["), key(KeyCode::Char('['), KeyModifiers::ALT));
+        assert_eq!(parse_one(b"\x1b]"), key(KeyCode::Char(']'), KeyModifiers::ALT));
+        assert_eq!(parse_one(b"\x1bO"), key(KeyCode::Char('O'), KeyModifiers::ALT));
+        assert_eq!(
+            parse(b"\x1bO5"),
+            [
+                key(KeyCode::Char('O'), KeyModifiers::ALT),
+                key(KeyCode::Char('5'), KeyModifiers::empty()),
+            ]
+        );
+        assert_eq!(
+            parse(b"\x1b[["),
+            [
+                key(KeyCode::Char('['), KeyModifiers::ALT),
+                key(KeyCode::Char('['), KeyModifiers::empty()),
+            ]
+        );
+    }
+
+    #[test]
+    fn prefix_without_key() {
+        assert_eq!(
+            parse(b"\x1bOz"),
+            [
+                key(KeyCode::Char('O'), KeyModifiers::ALT),
+                key(KeyCode::Char('z'), KeyModifiers::empty()),
+            ]
+        );
+        assert_eq!(
+            parse(b"\x1bO5z"),
+            [
+                key(KeyCode::Char('O'), KeyModifiers::ALT),
+                key(KeyCode::Char('5'), KeyModifiers::empty()),
+                key(KeyCode::Char('z'), KeyModifiers::empty()),
+            ]
+        );
+        assert_eq!(
+            parse(b"\x1b[[z"),
+            [
+                key(KeyCode::Char('['), KeyModifiers::ALT),
+                key(KeyCode::Char('['), KeyModifiers::empty()),
+                key(KeyCode::Char('z'), KeyModifiers::empty()),
+            ]
+        );
+    }
+
+    #[test]
+    fn escape_pending() {
+        let mut parser = Parser::new();
+        assert!(!parser.is_pending());
+
+        parser.advance(b"\x1b");
+        assert!(parser.is_pending());
+        assert_eq!(parser.next(), None);
+
+        parser.advance(b"[A");
+        assert!(!parser.is_pending());
+        assert_eq!(parser.next(), Some(key(KeyCode::Up, KeyModifiers::empty())));
+    }
+
+    #[test]
+    fn split_input() {
+        let mut parser = Parser::new();
+        for &byte in b"\x1b[1;5A\x1bOPa\x1b[97;5u" {
+            parser.advance(&[byte]);
+        }
+
+        assert_eq!(
+            parser.collect::<Vec<_>>(),
+            [
+                key(KeyCode::Up, KeyModifiers::CONTROL),
+                key(KeyCode::F(1), KeyModifiers::empty()),
+                key(KeyCode::Char('a'), KeyModifiers::empty()),
+                key(KeyCode::Char('a'), KeyModifiers::CONTROL),
+            ]
+        );
+    }
+
+    #[test]
+    fn escape_interrupts_sequence() {
+        assert_eq!(parse_one(b"\x1b[1;\x1b[B"), key(KeyCode::Down, KeyModifiers::empty()));
+        assert_eq!(parse_one(b"\x1b[1;\x1b"), key(KeyCode::Escape, KeyModifiers::empty()));
+    }
+
+    #[test]
+    fn unknown_device_control_string() {
+        assert_eq!(
+            parse_one(b"\x1bP1;2zabc\x1b\\"),
+            Event::Unknown(UnknownSequence::Dcs {
+                params: b"1;2".to_vec(),
+                intermediates: Vec::new(),
+                final_byte: b'z',
+                data: b"abc".to_vec(),
+            })
+        );
+        assert_eq!(
+            parse_one(b"\x1bP>;2 !x\x1b\\"),
+            Event::Unknown(UnknownSequence::Dcs {
+                params: b">;2".to_vec(),
+                intermediates: b" !".to_vec(),
+                final_byte: b'x',
+                data: Vec::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_sequences() {
+        assert_eq!(
+            parse_one(b"\x1b[?5x"),
+            Event::Unknown(UnknownSequence::Csi {
+                params: b"?5".to_vec(),
+                intermediates: Vec::new(),
+                final_byte: b'x',
+            })
+        );
+        // Rejected by the handler for their final byte.
+        assert_eq!(
+            parse_one(b"\x1b[1;999A"),
+            Event::Unknown(UnknownSequence::Csi {
+                params: b"1;999".to_vec(),
+                intermediates: Vec::new(),
+                final_byte: b'A',
+            })
+        );
+        assert_eq!(
+            parse_one(b"\x1b]777;x\x07"),
+            Event::Unknown(UnknownSequence::Osc { params: vec![b"777".to_vec(), b"x".to_vec()] })
+        );
+        assert_eq!(
+            parse_one(b"\x1b]11;nope\x07"),
+            Event::Unknown(UnknownSequence::Osc { params: vec![b"11".to_vec(), b"nope".to_vec()] })
+        );
+        assert_eq!(
+            parse_one(b"\x1b_zzz\x1b\\"),
+            Event::Unknown(UnknownSequence::Apc { data: b"zzz".to_vec() })
+        );
+    }
+
+    #[test]
+    fn desktop_notifications() {
+        assert_eq!(
+            parse_one(b"\x1b]99;i=abc:p=?;p=title,body\x1b\\"),
+            Event::DesktopNotifications(DesktopNotificationsSupport {
+                identifier: "abc".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn primary_device_attributes() {
+        assert_eq!(
+            parse_one(b"\x1b[?62;4;22c"),
+            Event::PrimaryDeviceAttributes(PrimaryDeviceAttributes {
+                conformance_level: 62,
+                features: DeviceFeatures::SIXEL_GRAPHICS | DeviceFeatures::ANSI_COLOR,
+                params: vec![62, 4, 22],
+            })
+        );
+        assert_eq!(
+            parse_one(b"\x1b[?1;2c"),
+            Event::PrimaryDeviceAttributes(PrimaryDeviceAttributes {
+                conformance_level: 1,
+                features: DeviceFeatures::empty(),
+                params: vec![1, 2],
+            })
+        );
+    }
+
+    #[test]
+    fn primary_device_attributes_empty_fields() {
+        // kitty replies with a trailing empty field.
+        assert_eq!(
+            parse_one(b"\x1b[?62;c"),
+            Event::PrimaryDeviceAttributes(PrimaryDeviceAttributes {
+                conformance_level: 62,
+                features: DeviceFeatures::empty(),
+                params: vec![62],
+            })
+        );
+        assert_eq!(
+            parse_one(b"\x1b[?62;;4c"),
+            Event::PrimaryDeviceAttributes(PrimaryDeviceAttributes {
+                conformance_level: 62,
+                features: DeviceFeatures::SIXEL_GRAPHICS,
+                params: vec![62, 4],
+            })
+        );
+    }
+
+    #[test]
+    fn secondary_device_attributes() {
+        assert_eq!(
+            parse_one(b"\x1b[>1;4000;0c"),
+            Event::SecondaryDeviceAttributes(SecondaryDeviceAttributes {
+                terminal_type: 1,
+                firmware_version: 4000,
+                rom_cartridge: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn terminal_version_reply() {
+        assert_eq!(
+            parse_one(b"\x1bP>|kitty(0.35.2)\x1b\\"),
+            Event::TerminalVersion(TerminalVersion {
+                name: "kitty".to_owned(),
+                version: Some("0.35.2".to_owned()),
+            })
+        );
+    }
+
+    #[test]
+    fn terminal_version_data() {
+        let version = |name: &str, version: Option<&str>| {
+            Some(TerminalVersion { name: name.to_owned(), version: version.map(str::to_owned) })
+        };
+
+        assert_eq!(terminal_version(b"XTerm(388)"), version("XTerm", Some("388")));
+        assert_eq!(terminal_version(b"tmux 3.4"), version("tmux", Some("3.4")));
+        assert_eq!(terminal_version(b"foot"), version("foot", None));
+        assert_eq!(terminal_version(b"WezTerm ()"), version("WezTerm", None));
+        assert_eq!(terminal_version(b"  "), None);
+    }
+
+    #[test]
+    fn mode_report() {
+        assert_eq!(
+            parse_one(b"\x1b[?2026;2$y"),
+            Event::ModeReport(ModeReport { mode: Mode::Dec(2026), state: ModeState::Reset })
+        );
+        assert_eq!(
+            parse_one(b"\x1b[4;1$y"),
+            Event::ModeReport(ModeReport { mode: Mode::Ansi(4), state: ModeState::Set })
+        );
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(
+            parse_one(b"\x1b]11;rgb:ffff/8080/0000\x07"),
+            Event::DynamicColor(DynamicColor {
+                kind: DynamicColorKind::Background,
+                color: RgbColor::new(0xffff, 0x8080, 0),
+            })
+        );
+        assert_eq!(
+            parse_one(b"\x1b]4;1;rgb:ff/00/00\x1b\\"),
+            Event::PaletteColor(PaletteColor { index: 1, color: RgbColor::new(0xffff, 0, 0) })
+        );
+    }
+
+    #[test]
+    fn cursor_position() {
+        assert_eq!(
+            parse_one(b"\x1b[2;5R"),
+            Event::CursorPosition(CursorPosition { column: 4, row: 1, page: None })
+        );
+        assert_eq!(
+            parse_one(b"\x1b[?2;5;1R"),
+            Event::CursorPosition(CursorPosition { column: 4, row: 1, page: Some(1) })
+        );
+    }
+
+    #[test]
+    fn cursor_position_on_first_row() {
+        // Without an expected report, this is shift+F3.
+        assert_eq!(parse_one(b"\x1b[1;2R"), key(KeyCode::F(3), KeyModifiers::SHIFT));
+
+        let mut parser = Parser::new();
+        parser.expect_cursor_position();
+        parser.advance(b"\x1b[1;2R\x1b[1;2R");
+        assert_eq!(
+            parser.by_ref().collect::<Vec<_>>(),
+            [
+                Event::CursorPosition(CursorPosition { column: 1, row: 0, page: None }),
+                key(KeyCode::F(3), KeyModifiers::SHIFT),
+            ]
+        );
+
+        parser.expect_cursor_position();
+        parser.clear_cursor_position_expectation();
+        parser.advance(b"\x1b[1;2R");
+        assert_eq!(parser.next(), Some(key(KeyCode::F(3), KeyModifiers::SHIFT)));
+    }
+
+    #[test]
+    fn window_size() {
+        assert_eq!(
+            parse_one(b"\x1b[4;100;200t"),
+            Event::WindowSize(WindowSizeReport {
+                kind: WindowSizeKind::TextAreaPixels,
+                width: 200,
+                height: 100,
+            })
+        );
+        assert_eq!(
+            parse_one(b"\x1b[6;10;20t"),
+            Event::WindowSize(WindowSizeReport {
+                kind: WindowSizeKind::CellPixels,
+                width: 20,
+                height: 10,
+            })
+        );
+        assert_eq!(
+            parse_one(b"\x1b[8;24;80t"),
+            Event::WindowSize(WindowSizeReport {
+                kind: WindowSizeKind::TextAreaCells,
+                width: 80,
+                height: 24,
+            })
+        );
+    }
+
+    #[test]
+    fn termcap_reply() {
+        assert_eq!(
+            parse_one(b"\x1bP1+r544E=787465726D;524742\x1b\\"),
+            Event::Termcap(TermcapReply::Valid(vec![
+                ("TN".to_owned(), Some("xterm".to_owned())),
+                ("RGB".to_owned(), None),
+            ]))
+        );
+        assert_eq!(parse_one(b"\x1bP0+r544E\x1b\\"), Event::Termcap(TermcapReply::Invalid));
+    }
+
+    #[test]
+    fn termcap_capabilities_data() {
+        assert_eq!(
+            termcap_capabilities(b"436F=323536"),
+            Some(vec![("Co".to_owned(), Some("256".to_owned()))])
+        );
+        assert_eq!(termcap_capabilities(b"524742"), Some(vec![("RGB".to_owned(), None)]));
+        assert_eq!(termcap_capabilities(b"5"), None);
+        assert_eq!(termcap_capabilities(b"ZZ"), None);
+    }
+
+    #[test]
+    fn status_string_reply() {
+        assert_eq!(
+            parse_one(b"\x1bP1$r2 q\x1b\\"),
+            Event::StatusString(StatusStringReply::Valid(Setting::CursorStyle(
+                CursorStyle::SteadyBlock
+            )))
+        );
+        assert_eq!(parse_one(b"\x1bP0$r\x1b\\"), Event::StatusString(StatusStringReply::Invalid));
+        assert_eq!(parse_one(b"\x1bP1$r\x1b\\"), Event::StatusString(StatusStringReply::Invalid));
+    }
+
+    #[test]
+    fn setting_data() {
+        assert_eq!(setting(b" q"), Some(Setting::CursorStyle(CursorStyle::Default)));
+        assert_eq!(setting(b"5 q"), Some(Setting::CursorStyle(CursorStyle::BlinkingBar)));
+        assert_eq!(setting(b"9 q"), None);
+        assert_eq!(
+            setting(b"0;1;38:2::255:0:0m"),
+            Some(Setting::Sgr("0;1;38:2::255:0:0".to_owned()))
+        );
+        assert_eq!(setting(b"1;24r"), Some(Setting::ScrollMargins { top: 0, bottom: 23 }));
+        assert_eq!(setting(b"0;24r"), None);
+        assert_eq!(setting(b"1;80s"), Some(Setting::LeftRightMargins { left: 0, right: 79 }));
+        assert_eq!(
+            setting(b"64;1\"p"),
+            Some(Setting::ConformanceLevel { level: 64, eight_bit_controls: Some(false) })
+        );
+        assert_eq!(
+            setting(b"65\"p"),
+            Some(Setting::ConformanceLevel { level: 65, eight_bit_controls: None })
+        );
+        assert_eq!(setting(b"x"), None);
+    }
+
+    #[test]
+    fn kitty_graphics() {
+        assert_eq!(
+            parse_one(b"\x1b_Gi=31;OK\x1b\\"),
+            Event::KittyGraphics(KittyGraphicsReply {
+                image_id: Some(31),
+                message: "OK".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn kitty_graphics_reply_data() {
+        assert_eq!(
+            kitty_graphics_reply(b"Gi=1,p=2;ENOENT:File not found"),
+            Some(KittyGraphicsReply {
+                image_id: Some(1),
+                message: "ENOENT:File not found".to_owned(),
+            })
+        );
+        assert_eq!(
+            kitty_graphics_reply(b"G;OK"),
+            Some(KittyGraphicsReply { image_id: None, message: "OK".to_owned() })
+        );
+        assert_eq!(kitty_graphics_reply(b"Gi=1"), None);
+        assert_eq!(kitty_graphics_reply(b"Hi=1;OK"), None);
+    }
+
+    #[test]
+    fn graphics_attribute() {
+        assert_eq!(
+            parse_one(b"\x1b[?1;0;256S"),
+            Event::GraphicsAttribute(GraphicsAttributeReport::ColorRegisters(256))
+        );
+        assert_eq!(
+            parse_one(b"\x1b[?2;0;800;600S"),
+            Event::GraphicsAttribute(GraphicsAttributeReport::SixelGeometry {
+                width: 800,
+                height: 600,
+            })
+        );
+    }
+
+    #[test]
+    fn theme() {
+        assert_eq!(parse_one(b"\x1b[?997;1n"), Event::ThemeChanged(Theme::Dark));
+        assert_eq!(parse_one(b"\x1b[?997;2n"), Event::ThemeChanged(Theme::Light));
+    }
 }
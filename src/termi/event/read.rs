@@ -24,12 +24,18 @@ where
 
 const TTY_BUFFER_LEN: usize = 1024;
 
+/// The default time to wait for the rest of an escape sequence before treating a lone escape as
+/// an Escape key press.
+pub const DEFAULT_ESCAPE_TIMEOUT: Duration = Duration::from_millis(100);
+
 pub struct EventLoop<'fd> {
     tty_fd: BorrowedFd<'fd>,
     tty_buffer: [MaybeUninit<u8>; TTY_BUFFER_LEN],
     parser: Parser,
     events: VecDeque<Event>,
     skipped_events: Vec<Event>,
+    escape_timeout: Duration,
+    last_input: Instant,
 }
 
 impl<'fd> EventLoop<'fd> {
@@ -42,9 +48,47 @@ impl<'fd> EventLoop<'fd> {
             parser: Parser::new(),
             events: VecDeque::with_capacity(32),
             skipped_events: Vec::with_capacity(32),
+            escape_timeout: DEFAULT_ESCAPE_TIMEOUT,
+            last_input: Instant::now(),
         })
     }
 
+    /// Returns the time to wait for the rest of an escape sequence.
+    pub fn escape_timeout(&self) -> Duration {
+        self.escape_timeout
+    }
+
+    /// Sets the time to wait for the rest of an escape sequence.
+    ///
+    /// If no more input arrives within this time, a partially received sequence is flushed from
+    /// the parser. A lone escape is then reported as an Escape key press.
+    pub fn set_escape_timeout(&mut self, timeout: Duration) {
+        self.escape_timeout = timeout;
+    }
+
+    /// Notes that a cursor position report has been requested with `CSI 6 n`.
+    ///
+    /// See [`Parser::expect_cursor_position`].
+    pub fn expect_cursor_position(&mut self) {
+        self.parser.expect_cursor_position();
+    }
+
+    /// Forgets any cursor position reports that are still expected.
+    ///
+    /// See [`Parser::clear_cursor_position_expectation`].
+    pub fn clear_cursor_position_expectation(&mut self) {
+        self.parser.clear_cursor_position_expectation();
+    }
+
+    /// Returns the time left before a partially received sequence should be flushed, if any.
+    fn escape_leftover(&self) -> Option<Duration> {
+        if self.parser.is_pending() {
+            Some(self.escape_timeout.saturating_sub(self.last_input.elapsed()))
+        } else {
+            None
+        }
+    }
+
     fn poll_internal(&self, timeout: Option<Duration>) -> io::Result<bool> {
         loop {
             // A bug in kernels < 2.6.37 makes timeouts larger than LONG_MAX / CONFIG_HZ
@@ -86,12 +130,19 @@ impl<'fd> EventLoop<'fd> {
 
         let mut leftover = timeout.leftover();
         loop {
-            match self.poll_internal(leftover) {
+            // Wake up in time to flush a partially received sequence.
+            let poll_timeout = match (leftover, self.escape_leftover()) {
+                (Some(leftover), Some(escape_leftover)) => Some(leftover.min(escape_leftover)),
+                (leftover, escape_leftover) => leftover.or(escape_leftover),
+            };
+
+            match self.poll_internal(poll_timeout) {
                 Ok(true) => 'read: loop {
                     match rustix::io::read_uninit(self.tty_fd, &mut self.tty_buffer) {
                         Ok((buf, _)) => {
                             if !buf.is_empty() {
                                 self.parser.advance(buf);
+                                self.last_input = Instant::now();
                             }
                         }
                         Err(err) => match err.kind() {
@@ -104,8 +155,22 @@ impl<'fd> EventLoop<'fd> {
                     if let Some(event) = self.parser.next() {
                         return Ok(Some(event));
                     }
+
+                    // Poll again rather than blocking on the next read, so that a partially
+                    // received sequence can be flushed once input goes idle.
+                    if self.parser.is_pending() {
+                        break 'read;
+                    }
                 },
-                Ok(false) => return Ok(None),
+                Ok(false) => {
+                    if self.escape_leftover().is_some_and(|t| t.is_zero()) {
+                        self.parser.flush();
+
+                        if let Some(event) = self.parser.next() {
+                            return Ok(Some(event));
+                        }
+                    }
+                }
                 Err(err) => {
                     if err.kind() != io::ErrorKind::Interrupted {
                         return Err(err);
@@ -154,7 +219,7 @@ impl<'fd> EventLoop<'fd> {
 
             leftover = timeout.leftover();
 
-            if leftover.map_or(false, |t| t.is_zero()) || event.is_some() {
+            if leftover.is_some_and(|t| t.is_zero()) || event.is_some() {
                 self.events.extend(self.skipped_events.drain(..));
 
                 if let Some(event) = event {
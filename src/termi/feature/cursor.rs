@@ -0,0 +1,39 @@
+use std::io;
+use std::os::fd::AsFd;
+
+use crate::event::{CursorPosition, Event, EventLoop};
+use crate::feature::{read_reply, write_query};
+use crate::terminal::{get_tty, set_raw_mode};
+
+/// Queries the current position of the cursor (`CPR`).
+///
+/// See <https://vt100.net/docs/vt510-rm/CPR.html>
+pub fn position() -> io::Result<CursorPosition> {
+    let tty = get_tty()?;
+    let tty = tty.as_fd();
+
+    let _guard = set_raw_mode(tty)?;
+
+    // ESC [ 6 n        Query cursor position.
+    const QUERY: &[u8] = b"\x1b[6n";
+
+    let mut event_loop = EventLoop::new(tty)?;
+    event_loop.expect_cursor_position();
+
+    write_query(QUERY)?;
+
+    let position = read_reply(
+        &mut event_loop,
+        |event| match event {
+            Event::CursorPosition(position) => Some(*position),
+            _ => None,
+        },
+        "Cursor position",
+    );
+
+    if position.is_err() {
+        event_loop.clear_cursor_position_expectation();
+    }
+
+    position
+}
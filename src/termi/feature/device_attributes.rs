@@ -0,0 +1,35 @@
+use std::io;
+use std::os::fd::AsFd;
+
+use crate::event::{Event, EventLoop, PrimaryDeviceAttributes};
+use crate::feature::{read_reply, write_query};
+use crate::terminal::{get_tty, set_raw_mode};
+
+/// Queries the primary device attributes (DA1) of the terminal.
+///
+/// Every terminal is expected to reply to this query, which is why it is used to detect the end
+/// of the replies to other queries.
+///
+/// See <https://vt100.net/docs/vt510-rm/DA1.html>
+pub fn query() -> io::Result<PrimaryDeviceAttributes> {
+    let tty = get_tty()?;
+    let tty = tty.as_fd();
+
+    let _guard = set_raw_mode(tty)?;
+
+    // ESC [ 0 c        Query primary device attributes.
+    const QUERY: &[u8] = b"\x1b[0c";
+
+    let mut event_loop = EventLoop::new(tty)?;
+
+    write_query(QUERY)?;
+
+    read_reply(
+        &mut event_loop,
+        |event| match event {
+            Event::PrimaryDeviceAttributes(attributes) => Some(attributes.clone()),
+            _ => None,
+        },
+        "Primary device attributes",
+    )
+}
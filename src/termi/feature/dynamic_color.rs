@@ -0,0 +1,32 @@
+use std::io;
+
+use crate::event::{DynamicColor, DynamicColorKind, Event, Filter, RgbColor};
+use crate::feature::query_replies;
+
+struct DynamicColorFilter(DynamicColorKind);
+
+impl Filter for DynamicColorFilter {
+    fn eval(&self, event: &Event) -> bool {
+        match event {
+            Event::DynamicColor(color) => color.kind == self.0,
+            _ => false,
+        }
+    }
+}
+
+/// Queries a dynamic color of the terminal, such as the background color.
+///
+/// Returns `None` if the terminal does not reply to the query.
+///
+/// See <https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h3-Operating-System-Commands>
+pub fn query(kind: DynamicColorKind) -> io::Result<Option<RgbColor>> {
+    // ESC ] Ps ; ? ESC \   Query dynamic color (10 = foreground, 11 = background, 12 = cursor).
+    let query = format!("\x1b]{};?\x1b\\", kind.code());
+
+    let replies = query_replies(query.as_bytes(), &DynamicColorFilter(kind), "Dynamic color")?;
+
+    Ok(replies.into_iter().find_map(|event| match event {
+        Event::DynamicColor(DynamicColor { color, .. }) => Some(color),
+        _ => None,
+    }))
+}
@@ -0,0 +1,16 @@
+use std::io;
+
+use crate::event::{Mode, ModeState};
+use crate::feature::mode;
+
+/// Queries the state of grapheme clustering (mode 2027).
+///
+/// When the mode is set, the terminal measures the width of text by grapheme clusters rather than
+/// by codepoints. Returns [`ModeState::NotRecognized`] if the terminal does not support `DECRQM`.
+///
+/// See <https://github.com/contour-terminal/terminal-unicode-core>
+pub fn query() -> io::Result<ModeState> {
+    let state = mode::query(Mode::Dec(2027))?;
+
+    Ok(state.unwrap_or(ModeState::NotRecognized))
+}
@@ -0,0 +1,154 @@
+use std::io;
+use std::os::fd::AsFd;
+use std::path::Path;
+
+use bitflags::bitflags;
+use rustix::fs::Mode;
+use rustix::shm;
+use uuid::Uuid;
+
+use crate::event::{Event, Filter};
+use crate::feature::query_replies;
+use crate::terminal::write_all;
+
+struct KittyGraphicsFilter;
+
+impl Filter for KittyGraphicsFilter {
+    fn eval(&self, event: &Event) -> bool {
+        matches!(event, Event::KittyGraphics(_))
+    }
+}
+
+bitflags! {
+    /// Represents the ways image data can be transmitted with the kitty graphics protocol.
+    ///
+    /// See <https://sw.kovidgoyal.net/kitty/graphics-protocol/#the-transmission-medium> for more information.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[repr(transparent)]
+    pub struct TransmissionMedia: u8 {
+        /// The data is sent directly in the escape code (`t=d`).
+        const DIRECT = 1 << 0;
+        /// The data is read from a file (`t=f`).
+        const FILE = 1 << 1;
+        /// The data is read from a temporary file, which the terminal deletes (`t=t`).
+        const TEMP_FILE = 1 << 2;
+        /// The data is read from a POSIX shared memory object (`t=s`).
+        const SHARED_MEMORY = 1 << 3;
+    }
+}
+
+/// The image ids used for the queries, one per transmission medium.
+const QUERY_IDS: [(u32, TransmissionMedia); 4] = [
+    (0x7e31, TransmissionMedia::DIRECT),
+    (0x7e32, TransmissionMedia::FILE),
+    (0x7e33, TransmissionMedia::TEMP_FILE),
+    (0x7e34, TransmissionMedia::SHARED_MEMORY),
+];
+
+/// The data of a single black pixel, in 24-bit RGB.
+const PIXEL: &[u8] = &[0, 0, 0];
+
+/// Queries which transmission media of the kitty graphics protocol the terminal supports.
+///
+/// Returns empty media if the terminal does not support the kitty graphics protocol.
+///
+/// See <https://sw.kovidgoyal.net/kitty/graphics-protocol/#querying-support-and-available-transmission-mediums>
+pub fn query() -> io::Result<TransmissionMedia> {
+    let mut identifier_buffer = [0u8; uuid::fmt::Simple::LENGTH];
+    let identifier: &str = Uuid::new_v4().simple().encode_lower(&mut identifier_buffer);
+
+    // The terminal only deletes temporary files with this in their name.
+    let file = std::env::temp_dir().join(format!("termi-{identifier}.rgb"));
+    let temp_file =
+        std::env::temp_dir().join(format!("termi-tty-graphics-protocol-{identifier}.rgb"));
+    let shm_name = format!("/termi-tty-graphics-protocol-{identifier}");
+
+    let result = query_media(&file, &temp_file, &shm_name);
+
+    // The terminal may have already deleted the temporary file and shared memory object.
+    let _ = std::fs::remove_file(&file);
+    let _ = std::fs::remove_file(&temp_file);
+    let _ = shm::unlink(shm_name.as_str());
+
+    result
+}
+
+fn query_media(file: &Path, temp_file: &Path, shm_name: &str) -> io::Result<TransmissionMedia> {
+    // Each medium is queried with a 1x1 image, which the terminal loads without storing it.
+    //
+    // ESC _ G i=<id>,s=1,v=1,a=q,t=<medium>,f=24 ; <payload> ESC \    Query graphics support.
+    //
+    // The payload is the base64 encoded image data for the direct medium, and the base64 encoded
+    // path or name of the data otherwise.
+    let mut query = Vec::with_capacity(512);
+
+    let mut push_query = |id: u32, medium: char, payload: &[u8]| {
+        query.extend_from_slice(format!("\x1b_Gi={id},s=1,v=1,a=q,t={medium},f=24;").as_bytes());
+        query.extend_from_slice(base64(payload).as_bytes());
+        query.extend_from_slice(b"\x1b\\");
+    };
+
+    let [direct, file_id, temp_file_id, shm_id] = QUERY_IDS.map(|(id, _)| id);
+
+    push_query(direct, 'd', PIXEL);
+
+    if std::fs::write(file, PIXEL).is_ok() {
+        push_query(file_id, 'f', file.as_os_str().as_encoded_bytes());
+    }
+
+    if std::fs::write(temp_file, PIXEL).is_ok() {
+        push_query(temp_file_id, 't', temp_file.as_os_str().as_encoded_bytes());
+    }
+
+    if write_shm(shm_name).is_ok() {
+        push_query(shm_id, 's', shm_name.as_bytes());
+    }
+
+    let replies = query_replies(&query, &KittyGraphicsFilter, "Graphics support")?;
+
+    let mut media = TransmissionMedia::empty();
+
+    for event in replies {
+        if let Event::KittyGraphics(reply) = event {
+            let medium = QUERY_IDS.iter().find(|(id, _)| reply.image_id == Some(*id));
+            if let (Some((_, medium)), true) = (medium, reply.is_ok()) {
+                media |= *medium;
+            }
+        }
+    }
+
+    Ok(media)
+}
+
+/// Creates a shared memory object containing a single pixel.
+fn write_shm(name: &str) -> io::Result<()> {
+    let fd = shm::open(
+        name,
+        shm::OFlags::CREATE | shm::OFlags::EXCL | shm::OFlags::RDWR,
+        Mode::RUSR | Mode::WUSR,
+    )?;
+
+    write_all(fd.as_fd(), PIXEL)
+}
+
+/// Encodes `data` as base64, with padding.
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
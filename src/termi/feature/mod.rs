@@ -1,2 +1,118 @@
+pub mod cursor;
 pub mod desktop_notifications;
+pub mod device_attributes;
+pub mod dynamic_color;
+pub mod grapheme_clustering;
+pub mod graphics;
 pub mod keyboard_enhancement;
+pub mod mode;
+pub mod palette;
+pub mod secondary_device_attributes;
+pub mod setting;
+pub mod sixel;
+pub mod synchronized_output;
+pub mod termcap;
+pub mod theme;
+pub mod window_geometry;
+pub mod xtversion;
+
+use std::io::{self, Write};
+use std::os::fd::AsFd;
+use std::time::Duration;
+
+use crate::event::{Event, EventLoop, Filter};
+use crate::terminal::{get_tty, open_dev_tty, set_raw_mode};
+
+struct PrimaryDeviceAttributesFilter;
+
+impl Filter for PrimaryDeviceAttributesFilter {
+    fn eval(&self, event: &Event) -> bool {
+        matches!(event, Event::PrimaryDeviceAttributes(_))
+    }
+}
+
+/// Writes a query to the terminal, falling back to stdout if `/dev/tty` cannot be opened.
+fn write_query(query: &[u8]) -> io::Result<()> {
+    let attempt = open_dev_tty().and_then(|mut fd| fd.write_all(query));
+
+    if attempt.is_err() {
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(query)?;
+        stdout.flush()?;
+    }
+
+    Ok(())
+}
+
+/// Sends a query to the terminal and collects the replies accepted by `filter`.
+///
+/// Not every terminal replies to every query, so the primary device attributes query is sent
+/// after it. Every terminal replies to that query, and replies arrive in the order the queries
+/// were sent, so once we receive the primary device attributes response, every reply has been
+/// received. A query that is not supported returns no replies, rather than waiting for a timeout.
+///
+/// `name` describes the replies in the error returned if the terminal does not respond in time.
+fn query_replies<F>(query: &[u8], filter: &F, name: &str) -> io::Result<Vec<Event>>
+where
+    F: Filter,
+{
+    let tty = get_tty()?;
+    let tty = tty.as_fd();
+
+    let _guard = set_raw_mode(tty)?;
+
+    let filter = |event: &Event| filter.eval(event) || PrimaryDeviceAttributesFilter.eval(event);
+
+    let mut event_loop = EventLoop::new(tty)?;
+
+    // ESC [ 0 c        Query primary device attributes.
+    let mut query = query.to_vec();
+    query.extend_from_slice(b"\x1b[0c");
+
+    write_query(&query)?;
+
+    let mut replies = Vec::new();
+
+    loop {
+        match event_loop.poll(Some(Duration::from_secs(2)), &filter) {
+            Ok(true) => match event_loop.read(&filter)? {
+                Event::PrimaryDeviceAttributes(_) => return Ok(replies),
+                event => replies.push(event),
+            },
+            Ok(false) => return Err(timed_out(name)),
+            Err(_) => {}
+        }
+    }
+}
+
+/// Reads the first event that `reply` takes a value from.
+///
+/// This is for replies that every terminal is expected to send, so unlike [`query_replies`] it
+/// does not wait for the primary device attributes response.
+///
+/// `name` describes the reply in the error returned if the terminal does not respond in time.
+fn read_reply<T, R>(event_loop: &mut EventLoop, reply: R, name: &str) -> io::Result<T>
+where
+    R: Fn(&Event) -> Option<T>,
+{
+    let filter = |event: &Event| reply(event).is_some();
+
+    loop {
+        match event_loop.poll(Some(Duration::from_secs(2)), &filter) {
+            Ok(true) => {
+                if let Some(reply) = reply(&event_loop.read(&filter)?) {
+                    return Ok(reply);
+                }
+            }
+            Ok(false) => return Err(timed_out(name)),
+            Err(_) => {}
+        }
+    }
+}
+
+fn timed_out(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::TimedOut,
+        format!("{name} could not be read within a normal duration"),
+    )
+}
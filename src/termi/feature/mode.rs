@@ -0,0 +1,49 @@
+use std::io::{self, Write};
+
+use crate::event::{Event, Filter, Mode, ModeReport, ModeState};
+use crate::feature::query_replies;
+
+struct ModeReportFilter;
+
+impl Filter for ModeReportFilter {
+    fn eval(&self, event: &Event) -> bool {
+        matches!(event, Event::ModeReport(_))
+    }
+}
+
+/// Queries the state of a terminal mode (`DECRQM`).
+///
+/// Returns `None` if the terminal does not support `DECRQM`.
+///
+/// See <https://vt100.net/docs/vt510-rm/DECRQM.html>
+pub fn query(mode: Mode) -> io::Result<Option<ModeState>> {
+    let reports = query_all(&[mode])?;
+
+    Ok(reports.into_iter().find(|report| report.mode == mode).map(|report| report.state))
+}
+
+/// Queries the state of several terminal modes in a single round trip (`DECRQM`).
+///
+/// Modes the terminal did not reply for are omitted, which is the case for every mode if the
+/// terminal does not support `DECRQM`.
+pub fn query_all(modes: &[Mode]) -> io::Result<Vec<ModeReport>> {
+    // ESC [ Pd $ p     Query ANSI mode.
+    // ESC [ ? Pd $ p   Query DEC private mode.
+    let mut query = Vec::new();
+    for mode in modes {
+        match mode {
+            Mode::Ansi(mode) => write!(query, "\x1b[{mode}$p")?,
+            Mode::Dec(mode) => write!(query, "\x1b[?{mode}$p")?,
+        }
+    }
+
+    let replies = query_replies(&query, &ModeReportFilter, "Mode states")?;
+
+    Ok(replies
+        .into_iter()
+        .filter_map(|event| match event {
+            Event::ModeReport(report) => Some(report),
+            _ => None,
+        })
+        .collect())
+}
@@ -0,0 +1,36 @@
+use std::io::{self, Write};
+
+use crate::event::{Event, Filter, PaletteColor};
+use crate::feature::query_replies;
+
+struct PaletteColorFilter;
+
+impl Filter for PaletteColorFilter {
+    fn eval(&self, event: &Event) -> bool {
+        matches!(event, Event::PaletteColor(_))
+    }
+}
+
+/// Queries colors of the 256-color palette in a single round trip.
+///
+/// Indices the terminal did not reply for are omitted, which is the case for every index if the
+/// terminal does not support palette queries.
+///
+/// See <https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h3-Operating-System-Commands>
+pub fn query(indices: &[u8]) -> io::Result<Vec<PaletteColor>> {
+    // ESC ] 4 ; Ps ; ? ESC \   Query palette color.
+    let mut query = Vec::new();
+    for index in indices {
+        write!(query, "\x1b]4;{index};?\x1b\\")?;
+    }
+
+    let replies = query_replies(&query, &PaletteColorFilter, "Palette colors")?;
+
+    Ok(replies
+        .into_iter()
+        .filter_map(|event| match event {
+            Event::PaletteColor(color) => Some(color),
+            _ => None,
+        })
+        .collect())
+}
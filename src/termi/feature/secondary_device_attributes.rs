@@ -0,0 +1,30 @@
+use std::io;
+
+use crate::event::{Event, Filter, SecondaryDeviceAttributes};
+use crate::feature::query_replies;
+
+struct SecondaryDeviceAttributesFilter;
+
+impl Filter for SecondaryDeviceAttributesFilter {
+    fn eval(&self, event: &Event) -> bool {
+        matches!(event, Event::SecondaryDeviceAttributes(_))
+    }
+}
+
+/// Queries the secondary device attributes (DA2) of the terminal.
+///
+/// Returns `None` if the terminal does not reply to the query.
+///
+/// See <https://vt100.net/docs/vt510-rm/DA2.html>
+pub fn query() -> io::Result<Option<SecondaryDeviceAttributes>> {
+    // ESC [ > c        Query secondary device attributes.
+    const QUERY: &[u8] = b"\x1b[>c";
+
+    let replies =
+        query_replies(QUERY, &SecondaryDeviceAttributesFilter, "Secondary device attributes")?;
+
+    Ok(replies.into_iter().find_map(|event| match event {
+        Event::SecondaryDeviceAttributes(attributes) => Some(attributes),
+        _ => None,
+    }))
+}
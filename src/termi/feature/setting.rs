@@ -0,0 +1,29 @@
+use std::io;
+
+use crate::event::{Event, Filter, Setting, SettingKind, StatusStringReply};
+use crate::feature::query_replies;
+
+struct StatusStringFilter;
+
+impl Filter for StatusStringFilter {
+    fn eval(&self, event: &Event) -> bool {
+        matches!(event, Event::StatusString(_))
+    }
+}
+
+/// Queries the current value of a setting (`DECRQSS`).
+///
+/// Returns `None` if the terminal does not recognize the setting or does not support `DECRQSS`.
+///
+/// See <https://vt100.net/docs/vt510-rm/DECRQSS.html>
+pub fn query(kind: SettingKind) -> io::Result<Option<Setting>> {
+    // ESC P $ q Pt ESC \   Query setting, where Pt is the control function of the setting.
+    let query = format!("\x1bP$q{}\x1b\\", kind.function());
+
+    let replies = query_replies(query.as_bytes(), &StatusStringFilter, "Setting")?;
+
+    Ok(replies.into_iter().find_map(|event| match event {
+        Event::StatusString(StatusStringReply::Valid(setting)) => Some(setting),
+        _ => None,
+    }))
+}
@@ -0,0 +1,63 @@
+use std::io;
+
+use crate::event::{DeviceFeatures, Event, Filter, GraphicsAttributeReport};
+use crate::feature::window_geometry::Size;
+use crate::feature::{device_attributes, query_replies};
+
+struct GraphicsAttributeFilter;
+
+impl Filter for GraphicsAttributeFilter {
+    fn eval(&self, event: &Event) -> bool {
+        matches!(event, Event::GraphicsAttribute(_))
+    }
+}
+
+/// Represents the sixel graphics capabilities of the terminal.
+///
+/// Each attribute is `None` if the terminal did not report it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SixelSupport {
+    /// The number of color registers.
+    pub color_registers: Option<u16>,
+    /// The largest image the terminal will display, in pixels.
+    pub max_geometry: Option<Size>,
+}
+
+/// Queries the sixel graphics capabilities of the terminal.
+///
+/// Returns `None` if the terminal does not report sixel graphics in its primary device
+/// attributes.
+///
+/// See <https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h3-Functions-using-CSI-_-ordered-by-the-final-character_s_>
+pub fn query() -> io::Result<Option<SixelSupport>> {
+    // A terminal that ignores the private marker would treat the graphics attribute queries as a
+    // scroll up (SU), so they are only sent once the primary device attributes report sixel
+    // graphics.
+    let attributes = device_attributes::query()?;
+
+    if !attributes.features.contains(DeviceFeatures::SIXEL_GRAPHICS) {
+        return Ok(None);
+    }
+
+    // ESC [ ? 1 ; 1 ; 0 S      Query number of color registers.
+    // ESC [ ? 2 ; 1 ; 0 S      Query maximum sixel geometry.
+    const QUERY: &[u8] = b"\x1b[?1;1;0S\x1b[?2;1;0S";
+
+    let replies = query_replies(QUERY, &GraphicsAttributeFilter, "Sixel graphics attributes")?;
+
+    let mut support = SixelSupport::default();
+
+    for event in replies {
+        match event {
+            Event::GraphicsAttribute(GraphicsAttributeReport::ColorRegisters(registers)) => {
+                support.color_registers = Some(registers)
+            }
+            Event::GraphicsAttribute(GraphicsAttributeReport::SixelGeometry { width, height }) => {
+                support.max_geometry = Some(Size { width, height })
+            }
+            _ => {}
+        }
+    }
+
+    Ok(Some(support))
+}
@@ -0,0 +1,13 @@
+use std::io;
+
+use crate::event::{Mode, ModeState};
+use crate::feature::mode;
+
+/// Queries whether the terminal supports synchronized output (mode 2026).
+///
+/// See <https://gist.github.com/christianparpart/d8a62cc1ab659194337d73e399004036>
+pub fn query() -> io::Result<bool> {
+    let state = mode::query(Mode::Dec(2026))?;
+
+    Ok(matches!(state, Some(ModeState::Set | ModeState::Reset | ModeState::PermanentlySet)))
+}
@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::event::{Event, Filter, TermcapReply};
+use crate::feature::query_replies;
+
+struct TermcapFilter;
+
+impl Filter for TermcapFilter {
+    fn eval(&self, event: &Event) -> bool {
+        matches!(event, Event::Termcap(_))
+    }
+}
+
+/// Queries terminfo capabilities of the terminal (`XTGETTCAP`), such as `TN`, `Co` or `RGB`.
+///
+/// Returns a map from capability name to value. Boolean capabilities have no value. Capabilities
+/// the terminal does not recognize are omitted, which is the case for every capability if the
+/// terminal does not support `XTGETTCAP`.
+///
+/// See <https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h3-Device-Control-functions>
+pub fn query(names: &[&str]) -> io::Result<HashMap<String, Option<String>>> {
+    // Each capability is queried separately, since some terminals stop at the first capability
+    // they do not recognize.
+    //
+    // ESC P + q Pt ESC \   Query terminfo capability, with the name hex encoded.
+    let mut query = Vec::new();
+    for name in names {
+        query.extend_from_slice(b"\x1bP+q");
+        for byte in name.bytes() {
+            write!(query, "{byte:02X}")?;
+        }
+        query.extend_from_slice(b"\x1b\\");
+    }
+
+    let replies = query_replies(&query, &TermcapFilter, "Terminfo capabilities")?;
+
+    let mut capabilities = HashMap::with_capacity(names.len());
+
+    for event in replies {
+        if let Event::Termcap(TermcapReply::Valid(caps)) = event {
+            capabilities.extend(caps);
+        }
+    }
+
+    Ok(capabilities)
+}
@@ -0,0 +1,53 @@
+use std::io;
+
+use crate::event::{DynamicColorKind, Event, Filter, Theme};
+use crate::feature::{dynamic_color, query_replies};
+
+/// The relative luminance above which a background color is considered light.
+///
+/// This is the luminance of a mid grey, perceptually halfway between black and white.
+const LIGHT_LUMINANCE: f64 = 0.18;
+
+struct ThemeFilter;
+
+impl Filter for ThemeFilter {
+    fn eval(&self, event: &Event) -> bool {
+        matches!(event, Event::ThemeChanged(_))
+    }
+}
+
+/// Queries the color scheme of the terminal.
+///
+/// If the terminal does not report its color scheme, it is guessed from the luminance of the
+/// background color instead. Returns `None` if neither is reported.
+///
+/// See <https://contour-terminal.org/vt-extensions/color-palette-update-notifications/>
+pub fn query() -> io::Result<Option<Theme>> {
+    if let Some(theme) = query_color_scheme()? {
+        return Ok(Some(theme));
+    }
+
+    let background = dynamic_color::query(DynamicColorKind::Background)?;
+
+    Ok(background.map(
+        |color| {
+            if color.luminance() > LIGHT_LUMINANCE {
+                Theme::Light
+            } else {
+                Theme::Dark
+            }
+        },
+    ))
+}
+
+fn query_color_scheme() -> io::Result<Option<Theme>> {
+    // ESC [ ? 996 n    Query color scheme.
+    const QUERY: &[u8] = b"\x1b[?996n";
+
+    let replies = query_replies(QUERY, &ThemeFilter, "Color scheme")?;
+
+    Ok(replies.into_iter().find_map(|event| match event {
+        Event::ThemeChanged(theme) => Some(theme),
+        _ => None,
+    }))
+}
@@ -0,0 +1,59 @@
+use std::io;
+
+use crate::event::{Event, Filter, WindowSizeKind, WindowSizeReport};
+use crate::feature::query_replies;
+
+struct WindowSizeFilter;
+
+impl Filter for WindowSizeFilter {
+    fn eval(&self, event: &Event) -> bool {
+        matches!(event, Event::WindowSize(_))
+    }
+}
+
+/// Represents a width and height.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Size {
+    pub width: u16,
+    pub height: u16,
+}
+
+/// Represents the geometry of the terminal window.
+///
+/// Each size is `None` if the terminal did not report it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct WindowGeometry {
+    /// The size of the text area in pixels.
+    pub text_area_pixels: Option<Size>,
+    /// The size of a character cell in pixels.
+    pub cell_pixels: Option<Size>,
+    /// The size of the text area in characters.
+    pub text_area_cells: Option<Size>,
+}
+
+/// Queries the geometry of the terminal window (`XTWINOPS`).
+///
+/// See <https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h3-Functions-using-CSI-_-ordered-by-the-final-character_s_>
+pub fn query() -> io::Result<WindowGeometry> {
+    // ESC [ 1 4 t      Query text area size in pixels.
+    // ESC [ 1 6 t      Query character cell size in pixels.
+    // ESC [ 1 8 t      Query text area size in characters.
+    const QUERY: &[u8] = b"\x1b[14t\x1b[16t\x1b[18t";
+
+    let replies = query_replies(QUERY, &WindowSizeFilter, "Window geometry")?;
+
+    let mut geometry = WindowGeometry::default();
+
+    for event in replies {
+        if let Event::WindowSize(WindowSizeReport { kind, width, height }) = event {
+            let size = Some(Size { width, height });
+            match kind {
+                WindowSizeKind::TextAreaPixels => geometry.text_area_pixels = size,
+                WindowSizeKind::CellPixels => geometry.cell_pixels = size,
+                WindowSizeKind::TextAreaCells => geometry.text_area_cells = size,
+            }
+        }
+    }
+
+    Ok(geometry)
+}
@@ -0,0 +1,29 @@
+use std::io;
+
+use crate::event::{Event, Filter, TerminalVersion};
+use crate::feature::query_replies;
+
+struct TerminalVersionFilter;
+
+impl Filter for TerminalVersionFilter {
+    fn eval(&self, event: &Event) -> bool {
+        matches!(event, Event::TerminalVersion(_))
+    }
+}
+
+/// Queries the name and version of the terminal (`XTVERSION`).
+///
+/// Returns `None` if the terminal does not reply to the query.
+///
+/// See <https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h3-Functions-using-CSI-_-ordered-by-the-final-character_s_>
+pub fn query() -> io::Result<Option<TerminalVersion>> {
+    // ESC [ > 0 q      Query terminal name and version (XTVERSION).
+    const QUERY: &[u8] = b"\x1b[>0q";
+
+    let replies = query_replies(QUERY, &TerminalVersionFilter, "Terminal version")?;
+
+    Ok(replies.into_iter().find_map(|event| match event {
+        Event::TerminalVersion(version) => Some(version),
+        _ => None,
+    }))
+}
@@ -1,5 +1,5 @@
-mod event;
 mod fd;
-mod terminal;
 
+pub mod event;
 pub mod feature;
+pub mod terminal;
@@ -1,8 +1,10 @@
 use std::io;
 
 use rustix::fs::{Mode, OFlags};
+use rustix::io::Errno;
 use rustix::termios::Termios;
 
+use crate::event::ModeState;
 use crate::fd::{AsFd, BorrowedFd, FileDesc, OwnedFd};
 
 pub fn open_dev_tty() -> io::Result<OwnedFd> {
@@ -53,3 +55,164 @@ pub fn set_raw_mode(fd: BorrowedFd) -> io::Result<RawModeGuard> {
 
     Ok(RawModeGuard { fd, original_ios })
 }
+
+/// Begins a synchronized update (mode 2026), returning a guard that ends it when dropped.
+///
+/// The terminal holds back rendering until the update ends, so that a frame is drawn at once.
+/// Nothing is written if `supported` is `false`, which should be the result of
+/// [`synchronized_output::query`](crate::feature::synchronized_output::query).
+///
+/// See <https://gist.github.com/christianparpart/d8a62cc1ab659194337d73e399004036>
+pub fn begin_synchronized_update(fd: BorrowedFd, supported: bool) -> io::Result<DecModeGuard> {
+    set_dec_modes(fd, if supported { &[2026] } else { &[] })
+}
+
+/// Writes all of `buf` to `fd`.
+pub(crate) fn write_all(fd: BorrowedFd, mut buf: &[u8]) -> io::Result<()> {
+    while !buf.is_empty() {
+        match rustix::io::write(fd, buf) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(n) => buf = &buf[n..],
+            Err(Errno::INTR) => continue,
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(())
+}
+
+fn write_dec_modes(fd: BorrowedFd, modes: &[u16], set: bool) -> io::Result<()> {
+    if modes.is_empty() {
+        return Ok(());
+    }
+
+    // ESC [ ? Pm h        Set DEC private mode(s).
+    // ESC [ ? Pm l        Reset DEC private mode(s).
+    let mut buf = String::from("\x1b[?");
+    for (i, mode) in modes.iter().enumerate() {
+        if i > 0 {
+            buf.push(';');
+        }
+        buf.push_str(&mode.to_string());
+    }
+    buf.push(if set { 'h' } else { 'l' });
+
+    write_all(fd, buf.as_bytes())
+}
+
+/// Resets a set of DEC private modes when dropped.
+pub struct DecModeGuard<'fd> {
+    fd: BorrowedFd<'fd>,
+    modes: Vec<u16>,
+}
+
+impl Drop for DecModeGuard<'_> {
+    fn drop(&mut self) {
+        let _ = write_dec_modes(self.fd, &self.modes, false);
+    }
+}
+
+/// Sets DEC private modes, returning a guard that resets them when dropped.
+pub fn set_dec_modes<'fd>(fd: BorrowedFd<'fd>, modes: &[u16]) -> io::Result<DecModeGuard<'fd>> {
+    write_dec_modes(fd, modes, true)?;
+    Ok(DecModeGuard { fd, modes: modes.to_vec() })
+}
+
+/// Represents which mouse events are reported by the terminal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseTracking {
+    /// Report button presses and releases (mode 1000).
+    Normal,
+    /// Additionally report motion while a button is held (mode 1002).
+    ButtonEvent,
+    /// Additionally report all motion (mode 1003).
+    AnyEvent,
+}
+
+impl MouseTracking {
+    fn mode(self) -> u16 {
+        match self {
+            Self::Normal => 1000,
+            Self::ButtonEvent => 1002,
+            Self::AnyEvent => 1003,
+        }
+    }
+}
+
+/// Represents how mouse events are encoded by the terminal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseEncoding {
+    /// The legacy encoding, limited to 223 rows and columns.
+    X10,
+    /// The urxvt encoding (mode 1015).
+    Urxvt,
+    /// The SGR encoding (mode 1006).
+    Sgr,
+    /// The SGR encoding, with positions reported in pixels (mode 1016).
+    SgrPixels,
+}
+
+impl MouseEncoding {
+    fn mode(self) -> Option<u16> {
+        match self {
+            Self::X10 => None,
+            Self::Urxvt => Some(1015),
+            Self::Sgr => Some(1006),
+            Self::SgrPixels => Some(1016),
+        }
+    }
+}
+
+/// Enables mouse reporting, returning a guard that disables it when dropped.
+///
+/// See <https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h2-Mouse-Tracking>
+pub fn enable_mouse_capture(
+    fd: BorrowedFd,
+    tracking: MouseTracking,
+    encoding: MouseEncoding,
+) -> io::Result<DecModeGuard> {
+    let modes = match encoding.mode() {
+        Some(encoding) => vec![tracking.mode(), encoding],
+        None => vec![tracking.mode()],
+    };
+    set_dec_modes(fd, &modes)
+}
+
+/// Enables bracketed paste (mode 2004), returning a guard that disables it when dropped.
+///
+/// See <https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h2-Bracketed-Paste-Mode>
+pub fn enable_bracketed_paste(fd: BorrowedFd) -> io::Result<DecModeGuard> {
+    set_dec_modes(fd, &[2004])
+}
+
+/// Enables focus reporting (mode 1004), returning a guard that disables it when dropped.
+///
+/// See <https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h2-FocusIn_FocusOut>
+pub fn enable_focus_reporting(fd: BorrowedFd) -> io::Result<DecModeGuard> {
+    set_dec_modes(fd, &[1004])
+}
+
+/// Enables grapheme clustering (mode 2027), returning a guard that restores it when dropped.
+///
+/// `state` should be the result of
+/// [`grapheme_clustering::query`](crate::feature::grapheme_clustering::query). The mode is only
+/// changed if it is currently reset, so the guard leaves the terminal as it was found.
+///
+/// See <https://github.com/contour-terminal/terminal-unicode-core>
+pub fn enable_grapheme_clustering(fd: BorrowedFd, state: ModeState) -> io::Result<DecModeGuard> {
+    let modes: &[u16] = match state {
+        ModeState::Reset => &[2027],
+        _ => &[],
+    };
+    set_dec_modes(fd, modes)
+}
+
+/// Enables color scheme update notifications (mode 2031), returning a guard that disables them
+/// when dropped.
+///
+/// While enabled, the terminal reports an [`Event::ThemeChanged`](crate::event::Event::ThemeChanged)
+/// whenever its color scheme changes.
+///
+/// See <https://contour-terminal.org/vt-extensions/color-palette-update-notifications/>
+pub fn enable_theme_notifications(fd: BorrowedFd) -> io::Result<DecModeGuard> {
+    set_dec_modes(fd, &[2031])
+}